@@ -1,3 +1,7 @@
+// The root finders below are kept as close as possible to their original
+// sources so the benchmark compares the actual implementations.
+#![allow(clippy::needless_return, clippy::manual_clamp)]
+
 use std::f32::consts::PI;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
@@ -2,12 +2,9 @@ use image::{DynamicImage, GenericImage, Rgba};
 use msdfont::{GlyphOutline, Offset, Scale, ShapeBuilder};
 
 fn main() {
-    let mut builder = ShapeBuilder::new(
-        10,
-        10,
-        Some(Scale::uniform(10.0)),
-        Offset::uniform(0.0),
-    );
+    let scale = Scale::uniform(10.0);
+    let mut builder =
+        ShapeBuilder::new(10, 10, Some(scale), Offset::uniform(0.0));
     builder.open_at(-5.0, 5.0);
     builder.line_to(10.0, 5.0);
     builder.line_to(10.0, 0.0);
@@ -19,12 +16,13 @@ fn main() {
 
     let (shape, bbox) = builder.build();
 
-    let glyph = GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
+    let glyph =
+        GlyphOutline::from_shape(shape, bbox, scale, Offset::uniform(0.0));
 
     let width = glyph.width() as u32;
     let height = glyph.height() as u32;
 
-    let sdf = glyph.generate_sdf(6).data();
+    let sdf = glyph.generate_sdf(6).unwrap().data();
 
     let mut image = DynamicImage::new_rgb8(width, height);
 
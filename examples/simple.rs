@@ -9,8 +9,8 @@ fn main() {
         .glyph('@')
         .build(Scale::uniform(100.0), Offset::uniform(7.0));
 
-    let bitmap = glyph.generate_sdf(14);
-    let sdf = bitmap.data;
+    let bitmap = glyph.generate_sdf(14).unwrap();
+    let sdf = bitmap.data();
     let width = bitmap.width;
     let height = bitmap.height;
 
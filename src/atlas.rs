@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+
+use rusttype::Scale;
+
+use crate::{
+    font::Font,
+    gen::{Bitmap, GenError},
+    vector::Vector2,
+    Offset,
+};
+
+/// Describes how glyph tiles are placed inside of an atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtlasLayout {
+    /// Tiles are packed as tightly as possible into rows (shelves),
+    /// tallest tiles first.
+    Tight,
+    /// Every tile is placed into its own fixed size `cell` (width, height)
+    /// on a regular lattice. Cells are filled left to right, top to bottom
+    /// in the order the characters were provided, so the cell of a glyph
+    /// can be found from its index alone.
+    ///
+    /// Use [`AtlasLayout::advance_grid`] to size the cells from the
+    /// glyph advances.
+    Grid { cell: (usize, usize) },
+}
+
+impl AtlasLayout {
+    /// Creates a [`AtlasLayout::Grid`] with cells wide enough to hold
+    /// the largest advance and tall enough to hold a whole line of the
+    /// provided characters, including the offset on every side.
+    ///
+    /// Cells are always large enough for the generated tiles, even if
+    /// some glyph overhangs its advance.
+    pub fn advance_grid(
+        font: &Font,
+        chars: &[char],
+        scale: Scale,
+        offset: Offset,
+    ) -> Self {
+        let v_metrics = font.v_metrics(scale);
+        let line_height = (v_metrics.ascent - v_metrics.descent).ceil();
+        // Same padding as the one added during generation.
+        let (pad_x, pad_y) = offset.padding();
+        let (pad_x, pad_y) = (pad_x as usize * 2, pad_y as usize * 2);
+
+        let mut cell = (0, line_height as usize + pad_y);
+        for &c in chars {
+            let glyph = font.glyph(c);
+            let advance = glyph.advance_width(scale).ceil() as usize;
+            let outline = glyph.build(scale, offset);
+            let (width, height) = (outline.width(), outline.height());
+            cell.0 = cell.0.max(advance.max(width.max(0) as usize) + pad_x);
+            cell.1 = cell.1.max(height.max(0) as usize + pad_y);
+        }
+
+        AtlasLayout::Grid { cell }
+    }
+}
+
+/// Location of a single glyph tile inside of the atlas bitmap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasGlyph {
+    /// Left edge of the tile in pixels.
+    pub x: usize,
+    /// Top edge of the tile in pixels.
+    pub y: usize,
+    /// Width of the tile in pixels.
+    pub width: usize,
+    /// Height of the tile in pixels.
+    pub height: usize,
+    /// Horizontal advance of the glyph at the atlas scale.
+    pub advance: f32,
+    /// Position of the glyph origin on the baseline relative to the top
+    /// left corner of the tile in pixels.
+    pub origin: Vector2<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtlasError {
+    /// There is no free space left for the tile of this character.
+    Full(char),
+    /// The tile of this character doesn't fit into a single grid cell.
+    CellTooSmall(char),
+    /// Generating the tile of this character failed.
+    Generation(char, GenError),
+}
+
+impl std::fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtlasError::Full(c) => {
+                write!(f, "Atlas Error: No space left for {:?}!", c)
+            }
+            AtlasError::CellTooSmall(c) => {
+                write!(f, "Atlas Error: {:?} doesn't fit into a cell!", c)
+            }
+            AtlasError::Generation(c, err) => {
+                write!(f, "Atlas Error: Generating {:?} failed: {}", c, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+/// Generated tile of a character with its advance and origin.
+type Tile = (char, f32, Vector2<f32>, Bitmap);
+
+/// Generates signed distance fields for a set of characters and packs
+/// them into a single bitmap.
+pub struct AtlasBuilder<'a, 'font> {
+    font: &'a Font<'font>,
+    scale: Scale,
+    offset: Offset,
+    range: usize,
+    layout: AtlasLayout,
+
+    bitmap: Bitmap,
+    glyphs: HashMap<char, AtlasGlyph>,
+    shelf: Shelf,
+    progress: Option<Box<dyn FnMut(usize, usize) + 'a>>,
+}
+
+/// The last shelf of the [`AtlasLayout::Tight`] packing, which is still
+/// open for more tiles.
+#[derive(Debug, Default, Clone, Copy)]
+struct Shelf {
+    /// Left edge of the free space on the shelf.
+    x: usize,
+    /// Top edge of the shelf.
+    y: usize,
+    /// Height of the tallest tile on the shelf.
+    height: usize,
+}
+
+impl<'a, 'font> AtlasBuilder<'a, 'font> {
+    /// Number of floats per glyph in [`AtlasBuilder::to_gpu_buffer`].
+    pub const GPU_STRIDE: usize = 9;
+
+    /// Creates an empty atlas with the provided dimensions in pixels.
+    ///
+    /// `scale`, `offset` and `range` are used for every glyph the same way
+    /// as in [`crate::Glyph::build`] and
+    /// [`crate::GlyphOutline::generate_sdf`].
+    pub fn new(
+        font: &'a Font<'font>,
+        width: usize,
+        height: usize,
+        scale: Scale,
+        offset: Offset,
+        range: usize,
+        layout: AtlasLayout,
+    ) -> Self {
+        let bitmap = Bitmap::new(width as u32, height as u32, 1, range as f32);
+        Self {
+            font,
+            scale,
+            offset,
+            range,
+            layout,
+
+            bitmap,
+            glyphs: HashMap::new(),
+            shelf: Shelf::default(),
+            progress: None,
+        }
+    }
+
+    /// Sets a callback invoked after a glyph is generated during
+    /// [`AtlasBuilder::build`] with the number of generated glyphs and the
+    /// number of glyphs the build generates in total.
+    pub fn set_progress<F>(&mut self, callback: F)
+    where
+        F: FnMut(usize, usize) + 'a,
+    {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /// Generates and places a tile for every provided character.
+    ///
+    /// Characters which are already in the atlas are skipped.
+    pub fn build(&mut self, chars: &[char]) -> Result<(), AtlasError> {
+        let mut new_chars: Vec<char> = Vec::new();
+        for &c in chars {
+            if !self.glyphs.contains_key(&c) && !new_chars.contains(&c) {
+                new_chars.push(c);
+            }
+        }
+
+        let mut tiles: Vec<Tile> = Vec::new();
+        for (i, &c) in new_chars.iter().enumerate() {
+            let glyph = self.font.glyph(c);
+            let advance = glyph.advance_width(self.scale);
+            let outline = glyph.build(self.scale, self.offset);
+            let bitmap = outline
+                .generate_sdf(self.range)
+                .map_err(|err| AtlasError::Generation(c, err))?;
+            tiles.push((c, advance, outline.origin, bitmap));
+            if let Some(progress) = self.progress.as_mut() {
+                progress(i + 1, new_chars.len());
+            }
+        }
+
+        let positions = match self.layout {
+            AtlasLayout::Tight => {
+                // Packing the tallest tiles first keeps shelves compact.
+                tiles.sort_by_key(|t| std::cmp::Reverse(t.3.height));
+                let (positions, shelf) = self.pack_tight(&tiles)?;
+                self.shelf = shelf;
+                positions
+            }
+            AtlasLayout::Grid { cell } => self.pack_grid(&tiles, cell)?,
+        };
+
+        for ((c, advance, origin, bitmap), (x, y)) in
+            tiles.iter().zip(positions)
+        {
+            self.bitmap.blit(bitmap, x, y);
+            self.glyphs.insert(
+                *c,
+                AtlasGlyph {
+                    x,
+                    y,
+                    width: bitmap.width as usize,
+                    height: bitmap.height as usize,
+                    advance: *advance,
+                    origin: *origin,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Generates and places a tile for a single character into the space
+    /// left after the already placed tiles, without moving any of them,
+    /// and returns its location.
+    ///
+    /// Useful for growing a glyph cache as new characters show up. If the
+    /// character is already in the atlas, its location is returned.
+    pub fn insert_glyph(&mut self, c: char) -> Result<AtlasGlyph, AtlasError> {
+        self.build(&[c])?;
+        Ok(self.glyphs[&c])
+    }
+
+    /// Returns the location of the provided character in the atlas.
+    pub fn glyph(&self, c: char) -> Option<&AtlasGlyph> {
+        self.glyphs.get(&c)
+    }
+
+    /// Returns all glyphs in the atlas, ordered by character, packed into
+    /// a flat array for uploading to the GPU, together with the characters
+    /// in the same order.
+    ///
+    /// Every glyph takes [`AtlasBuilder::GPU_STRIDE`] floats:
+    ///
+    /// - `x`, `y`, `width` and `height` of the tile in the atlas,
+    /// - `left`, `top`, `right` and `bottom` plane bounds of the tile
+    ///   relative to the glyph origin, with the y axis pointing down,
+    /// - the horizontal `advance`.
+    ///
+    /// All values are in pixels at the atlas scale.
+    pub fn to_gpu_buffer(&self) -> (Vec<char>, Vec<f32>) {
+        let mut chars: Vec<char> = self.glyphs.keys().copied().collect();
+        chars.sort_unstable();
+
+        let mut buffer = Vec::with_capacity(chars.len() * Self::GPU_STRIDE);
+        for c in &chars {
+            let g = &self.glyphs[c];
+            let (width, height) = (g.width as f32, g.height as f32);
+            buffer.extend_from_slice(&[
+                g.x as f32,
+                g.y as f32,
+                width,
+                height,
+                -g.origin.x,
+                -g.origin.y,
+                width - g.origin.x,
+                height - g.origin.y,
+                g.advance,
+            ]);
+        }
+
+        (chars, buffer)
+    }
+
+    /// Copies every glyph of the atlas into its own bitmap of `layer_size`
+    /// pixels, for uploading as a texture array instead of a packed atlas,
+    /// and returns the layers with the layer of every character.
+    ///
+    /// Layers are ordered by character and every tile is placed at the top
+    /// left corner of its layer, so the `origin` of its [`AtlasGlyph`]
+    /// still applies. The rest of a layer is as far outside as the range
+    /// allows, so neighbouring glyphs can't bleed into each other.
+    pub fn build_layered(
+        &self,
+        layer_size: (usize, usize),
+    ) -> Result<(Vec<Bitmap>, HashMap<char, usize>), AtlasError> {
+        let mut chars: Vec<char> = self.glyphs.keys().copied().collect();
+        chars.sort_unstable();
+
+        let (width, height) = layer_size;
+        let atlas_width = self.bitmap.width as usize;
+        let mut layers = Vec::with_capacity(chars.len());
+        let mut indices = HashMap::with_capacity(chars.len());
+        for c in chars {
+            let g = &self.glyphs[&c];
+            if g.width > width || g.height > height {
+                return Err(AtlasError::CellTooSmall(c));
+            }
+            let mut layer =
+                Bitmap::new(width as u32, height as u32, 1, self.range as f32);
+            for row in 0..g.height {
+                let from = (g.y + row) * atlas_width + g.x;
+                layer.distances[row * width..row * width + g.width]
+                    .copy_from_slice(
+                        &self.bitmap.distances[from..from + g.width],
+                    );
+            }
+            indices.insert(c, layers.len());
+            layers.push(layer);
+        }
+
+        Ok((layers, indices))
+    }
+
+    /// Returns the atlas bitmap.
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    pub fn layout(&self) -> AtlasLayout {
+        self.layout
+    }
+
+    /// Places tiles on the last shelf and on new shelves below it and
+    /// returns their positions together with the last shelf afterwards.
+    fn pack_tight(
+        &self,
+        tiles: &[Tile],
+    ) -> Result<(Vec<(usize, usize)>, Shelf), AtlasError> {
+        let width = self.bitmap.width as usize;
+        let height = self.bitmap.height as usize;
+        let mut shelf = self.shelf;
+
+        let mut positions = Vec::with_capacity(tiles.len());
+        for (c, _, _, tile) in tiles {
+            let (w, h) = (tile.width as usize, tile.height as usize);
+            if shelf.x + w > width {
+                shelf = Shelf {
+                    x: 0,
+                    y: shelf.y + shelf.height,
+                    height: 0,
+                };
+            }
+            if shelf.x + w > width || shelf.y + h > height {
+                return Err(AtlasError::Full(*c));
+            }
+            positions.push((shelf.x, shelf.y));
+            shelf.height = shelf.height.max(h);
+            shelf.x += w;
+        }
+
+        Ok((positions, shelf))
+    }
+
+    /// Places tiles into the cells following the already occupied ones.
+    fn pack_grid(
+        &self,
+        tiles: &[Tile],
+        cell: (usize, usize),
+    ) -> Result<Vec<(usize, usize)>, AtlasError> {
+        let columns = self.bitmap.width as usize / cell.0.max(1);
+        let rows = self.bitmap.height as usize / cell.1.max(1);
+
+        let mut positions = Vec::with_capacity(tiles.len());
+        for (i, (c, _, _, tile)) in tiles.iter().enumerate() {
+            if tile.width as usize > cell.0 || tile.height as usize > cell.1 {
+                return Err(AtlasError::CellTooSmall(*c));
+            }
+            let index = self.glyphs.len() + i;
+            if columns == 0 || index / columns >= rows {
+                return Err(AtlasError::Full(*c));
+            }
+            positions
+                .push((index % columns * cell.0, index / columns * cell.1));
+        }
+
+        Ok(positions)
+    }
+}
+
+#[test]
+fn grid_layout_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data);
+    let chars = ['A', 'B', 'W', 'i', 'l', 'm', 'x', '0', '1', '@'];
+    let scale = Scale::uniform(24.0);
+    let offset = Offset::uniform(2.0);
+
+    let layout = AtlasLayout::advance_grid(&font, &chars, scale, offset);
+    let cell = match layout {
+        AtlasLayout::Grid { cell } => cell,
+        AtlasLayout::Tight => unreachable!(),
+    };
+    let mut atlas = AtlasBuilder::new(
+        &font,
+        cell.0 * 4,
+        cell.1 * 3,
+        scale,
+        offset,
+        4,
+        layout,
+    );
+    atlas.build(&chars).unwrap();
+
+    for (i, c) in chars.iter().enumerate() {
+        let glyph = atlas.glyph(*c).unwrap();
+        assert_eq!(glyph.x, i % 4 * cell.0);
+        assert_eq!(glyph.y, i / 4 * cell.1);
+        assert!(glyph.width <= cell.0 && glyph.height <= cell.1);
+    }
+}
+
+#[test]
+fn progress_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data);
+    let calls = std::cell::RefCell::new(Vec::new());
+
+    let mut atlas = AtlasBuilder::new(
+        &font,
+        256,
+        256,
+        Scale::uniform(16.0),
+        Offset::uniform(2.0),
+        4,
+        AtlasLayout::Tight,
+    );
+    atlas.set_progress(|done, total| calls.borrow_mut().push((done, total)));
+    // Duplicates are only generated once.
+    atlas.build(&['a', 'b', 'a', 'c']).unwrap();
+    // Already generated glyphs are skipped.
+    atlas.build(&['c', 'd']).unwrap();
+    drop(atlas);
+
+    assert_eq!(calls.into_inner(), vec![(1, 3), (2, 3), (3, 3), (1, 1)]);
+}
+
+#[test]
+fn gpu_buffer_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data);
+    let mut atlas = AtlasBuilder::new(
+        &font,
+        256,
+        256,
+        Scale::uniform(24.0),
+        Offset::uniform(2.0),
+        4,
+        AtlasLayout::Tight,
+    );
+    atlas.build(&['g', 'A', '.', 'x']).unwrap();
+
+    let (chars, buffer) = atlas.to_gpu_buffer();
+    assert_eq!(chars, vec!['.', 'A', 'g', 'x']);
+    assert_eq!(buffer.len(), chars.len() * AtlasBuilder::GPU_STRIDE);
+
+    for (c, values) in chars.iter().zip(buffer.chunks(AtlasBuilder::GPU_STRIDE))
+    {
+        let glyph = atlas.glyph(*c).unwrap();
+        assert_eq!(values[0], glyph.x as f32);
+        assert_eq!(values[8], glyph.advance);
+        // The plane bounds have the size of the tile.
+        assert_eq!(values[6] - values[4], values[2]);
+        assert_eq!(values[7] - values[5], values[3]);
+    }
+    // 'g' descends below the baseline.
+    let g = &buffer[2 * AtlasBuilder::GPU_STRIDE..];
+    assert!(g[5] < 0.0 && g[7] > 0.0);
+}
+
+#[test]
+fn insert_glyph_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data);
+    let mut atlas = AtlasBuilder::new(
+        &font,
+        96,
+        64,
+        Scale::uniform(24.0),
+        Offset::uniform(2.0),
+        4,
+        AtlasLayout::Tight,
+    );
+
+    let mut placed: Vec<AtlasGlyph> = Vec::new();
+    let mut full = None;
+    for c in "abcdefghijklmnopqrstuvwxyz".chars() {
+        match atlas.insert_glyph(c) {
+            Ok(glyph) => {
+                assert!(glyph.x + glyph.width <= 96);
+                assert!(glyph.y + glyph.height <= 64);
+                for other in &placed {
+                    assert!(
+                        glyph.x >= other.x + other.width
+                            || other.x >= glyph.x + glyph.width
+                            || glyph.y >= other.y + other.height
+                            || other.y >= glyph.y + glyph.height
+                    );
+                }
+                placed.push(glyph);
+            }
+            Err(err) => {
+                full = Some(err);
+                break;
+            }
+        }
+    }
+    // Tiles are added to the same shelf until it is full.
+    assert_eq!((placed[0].y, placed[1].y), (0, 0));
+    assert!(matches!(full, Some(AtlasError::Full(_))));
+    // Inserting a character which is already there doesn't place it again.
+    assert_eq!(atlas.insert_glyph('a').unwrap(), placed[0]);
+}
+
+#[test]
+fn layered_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data);
+    let mut atlas = AtlasBuilder::new(
+        &font,
+        256,
+        256,
+        Scale::uniform(24.0),
+        Offset::uniform(2.0),
+        4,
+        AtlasLayout::Tight,
+    );
+    let chars = ['W', 'g', '.', 'i'];
+    atlas.build(&chars).unwrap();
+
+    let (layers, indices) = atlas.build_layered((32, 40)).unwrap();
+    assert_eq!(layers.len(), chars.len());
+    assert_eq!(indices.len(), chars.len());
+    for c in chars {
+        let layer = &layers[indices[&c]];
+        assert_eq!((layer.width, layer.height), (32, 40));
+
+        // The tile sits in the top left corner of its layer.
+        let g = atlas.glyph(c).unwrap();
+        for y in 0..g.height {
+            for x in 0..g.width {
+                assert_eq!(
+                    layer.distance(x as u32, y as u32),
+                    atlas.bitmap().distance((g.x + x) as u32, (g.y + y) as u32)
+                );
+            }
+        }
+        assert_eq!(layer.distance(31, 39), -2.0);
+    }
+
+    assert_eq!(
+        atlas.build_layered((4, 4)).unwrap_err(),
+        AtlasError::CellTooSmall('.')
+    );
+}
@@ -0,0 +1,363 @@
+use crate::shape::{Contour, Segment, Shape};
+
+/// Angle in radians between two segments, measured inside of their joint,
+/// below which the joint is a corner.
+///
+/// Same as in `msdfgen`, the value `3.0` makes every joint where the
+/// direction changes by more than `PI - 3.0`, about 8 degrees, a corner.
+pub const DEFAULT_ANGLE_THRESHOLD: f32 = 3.0;
+
+/// Part of a segment next to a joint whose turning is compared with the
+/// turn at the joint.
+const CURVATURE_STEP: f32 = 0.1;
+
+/// Color of an edge used for multi-channel distance fields.
+///
+/// Each bit represents one of the RGB channels the edge contributes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeColor {
+    Black = 0b000,
+    Red = 0b001,
+    Green = 0b010,
+    Yellow = 0b011,
+    Blue = 0b100,
+    Magenta = 0b101,
+    Cyan = 0b110,
+    White = 0b111,
+}
+
+impl EdgeColor {
+    /// Returns which of the RGB channels the color contributes to.
+    #[inline]
+    pub fn channels(self) -> [bool; 3] {
+        let bits = self as u8;
+        [bits & 0b001 != 0, bits & 0b010 != 0, bits & 0b100 != 0]
+    }
+
+    /// Maps cyan to magenta, magenta to yellow and yellow to cyan, leaving
+    /// the other colors as they are.
+    ///
+    /// Rotating all colors of a contour keeps neighbouring segments
+    /// different.
+    fn rotate(self) -> EdgeColor {
+        match self {
+            EdgeColor::Cyan => EdgeColor::Magenta,
+            EdgeColor::Magenta => EdgeColor::Yellow,
+            EdgeColor::Yellow => EdgeColor::Cyan,
+            other => other,
+        }
+    }
+
+    /// Returns the next color out of cyan, magenta and yellow which
+    /// differs from both this color and the `banned` one.
+    ///
+    /// Any two of those colors share exactly one channel.
+    fn switch(self, banned: EdgeColor) -> EdgeColor {
+        const CYCLE: [EdgeColor; 3] =
+            [EdgeColor::Cyan, EdgeColor::Magenta, EdgeColor::Yellow];
+        let start = CYCLE.iter().position(|c| *c == self).map_or(0, |i| i + 1);
+
+        (0..CYCLE.len())
+            .map(|i| CYCLE[(start + i) % CYCLE.len()])
+            .find(|c| *c != self && *c != banned)
+            .expect("Coloring Error: No color available!")
+    }
+}
+
+/// Assigns a color to every segment of the contour.
+///
+/// Segments which meet at a corner, a joint where the angle between them is
+/// below `angle_threshold` radians, always get different colors so the
+/// corner stays sharp in the multi-channel distance field. Smooth
+/// contours stay [`EdgeColor::White`].
+///
+/// A joint which turns less than either of its segments turns right next
+/// to it isn't a corner either, so a finely subdivided curve with slightly
+/// mismatched tangents stays smooth.
+pub fn color_contour(
+    contour: &Contour,
+    angle_threshold: f32,
+) -> Vec<EdgeColor> {
+    let segments = &contour.segments;
+    // The direction changes by more than `PI - angle_threshold` at a
+    // corner, which has the same sine.
+    let cross_threshold = angle_threshold.sin();
+
+    // Corners are stored as indices of the segments which start at them.
+    let corners = (0..segments.len())
+        .filter(|&i| {
+            let before = &segments[(i + segments.len() - 1) % segments.len()];
+            let prev = before.direction_at(1.0);
+            let next = segments[i].direction_at(0.0);
+            if prev.is_zero() || next.is_zero() {
+                return false;
+            }
+            let (prev, next) = (prev.normalize(), next.normalize());
+            if prev.dot(next) <= 0.0 {
+                return true;
+            }
+            let curvature = turning(before, 1.0 - CURVATURE_STEP, 1.0)
+                .max(turning(&segments[i], 0.0, CURVATURE_STEP));
+            prev.cross(next).abs() > cross_threshold
+                && prev.angle_between(next) > curvature
+        })
+        .collect::<Vec<_>>();
+
+    let mut colors = vec![EdgeColor::White; segments.len()];
+    match corners.len() {
+        0 => (),
+        // A "teardrop" with a single corner is split into three parts so
+        // the segments on both sides of the corner differ.
+        1 => {
+            let start = corners[0];
+            let first = EdgeColor::White.switch(EdgeColor::Black);
+            let last = first.switch(EdgeColor::Black);
+            let count = segments.len();
+            for i in 0..count {
+                let third = (3 * i + count / 2) / count.max(1);
+                colors[(start + i) % count] = match third {
+                    0 => first,
+                    1 => EdgeColor::White,
+                    _ => last,
+                };
+            }
+        }
+        _ => {
+            let start = corners[0];
+            let initial = EdgeColor::White.switch(EdgeColor::Black);
+            let mut color = initial;
+            let mut corner = 0;
+            for i in 0..segments.len() {
+                let index = (start + i) % segments.len();
+                if corner + 1 < corners.len() && corners[corner + 1] == index {
+                    corner += 1;
+                    // The last spline also touches the first one.
+                    let banned = if corner == corners.len() - 1 {
+                        initial
+                    } else {
+                        EdgeColor::Black
+                    };
+                    color = color.switch(banned);
+                }
+                colors[index] = color;
+            }
+        }
+    }
+
+    colors
+}
+
+/// Assigns a color to every segment of every contour of the shape, in the
+/// order of the contours.
+///
+/// Every contour is colored the same way as by [`color_contour`], but the
+/// colors are then made compatible with the contours colored before it.
+/// Where a contour comes closest to an earlier contour, the two segments
+/// must not share the same color, otherwise the channels of a thin gap
+/// between a fill and a hole can't tell the contours apart. Colors of a
+/// contour with corners are rotated through cyan, magenta and yellow, which
+/// keeps its corners sharp, and a smooth contour gets a single color.
+pub fn color_shape(shape: &Shape, angle_threshold: f32) -> Vec<Vec<EdgeColor>> {
+    let mut colors: Vec<Vec<EdgeColor>> =
+        Vec::with_capacity(shape.contours.len());
+    for (i, contour) in shape.contours.iter().enumerate() {
+        let mut own = color_contour(contour, angle_threshold);
+        let closest = shape.contours[..i]
+            .iter()
+            .enumerate()
+            .filter_map(|(j, other)| {
+                closest_segments(contour, other).map(|(a, b, d)| (a, j, b, d))
+            })
+            .min_by(|a, b| a.3.total_cmp(&b.3));
+
+        if let Some((a, j, b, _)) = closest {
+            let banned = colors[j][b];
+            if own.iter().all(|c| *c == EdgeColor::White) {
+                own.fill(EdgeColor::White.switch(banned));
+            } else {
+                for _ in 0..2 {
+                    if own[a] != banned {
+                        break;
+                    }
+                    own.iter_mut().for_each(|c| *c = c.rotate());
+                }
+            }
+        }
+        colors.push(own);
+    }
+
+    colors
+}
+
+/// Returns the angle in radians between the directions of the segment at
+/// the parameters `t0` and `t1`, which is zero for lines.
+fn turning(segment: &Segment, t0: f32, t1: f32) -> f32 {
+    let (start, end) = (segment.direction_at(t0), segment.direction_at(t1));
+    if start.is_zero() || end.is_zero() {
+        0.0
+    } else {
+        start.angle_between(end)
+    }
+}
+
+/// Returns the indices of the segments where the contour `a` comes closest
+/// to the contour `b` together with the distance between them, or [`None`]
+/// if either contour has no segments.
+///
+/// The distance is found from samples along the segments of `a`.
+fn closest_segments(a: &Contour, b: &Contour) -> Option<(usize, usize, f32)> {
+    const SAMPLES: usize = 8;
+    a.iter()
+        .enumerate()
+        .flat_map(|(s, segment)| {
+            (0..=SAMPLES).filter_map(move |k| {
+                let point = segment.point_at(k as f32 / SAMPLES as f32);
+                b.closest_segment(point)
+                    .map(|(other, distance)| (s, other, distance.real_dist))
+            })
+        })
+        .min_by(|x, y| x.2.total_cmp(&y.2))
+}
+
+#[test]
+fn rectangle_coloring_test() {
+    let outline = crate::gen::rect_outline(16, 2.0);
+    let colors =
+        color_contour(&outline.shape.contours[0], DEFAULT_ANGLE_THRESHOLD);
+
+    assert_eq!(colors.len(), 4);
+    for i in 0..colors.len() {
+        assert_ne!(colors[i], EdgeColor::White);
+        assert_ne!(colors[i], colors[(i + 1) % colors.len()]);
+    }
+}
+
+#[test]
+fn subdivided_circle_coloring_test() {
+    use crate::{shape::Quad, vector::Vector2, Winding};
+
+    // Control points slightly off the tangents make every joint turn by
+    // about half a degree, less than the quads turn right next to it.
+    const COUNT: usize = 24;
+    let step = std::f32::consts::TAU / COUNT as f32;
+    let point = |angle: f32, radius: f32| {
+        Vector2::new(20.0 + radius * angle.cos(), 20.0 + radius * angle.sin())
+    };
+    let segments = (0..COUNT)
+        .map(|i| {
+            let angle = i as f32 * step;
+            Segment::Quad(Quad::new(
+                point(angle, 10.0),
+                point(angle + 0.5 * step, 10.08),
+                point(angle + step, 10.0),
+            ))
+        })
+        .collect();
+    let contour = Contour::new(segments, Winding(true));
+
+    // The threshold alone would make every joint a corner.
+    let colors = color_contour(&contour, std::f32::consts::PI - 0.005);
+    assert!(colors.iter().all(|c| *c == EdgeColor::White));
+}
+
+#[test]
+fn sharp_joint_coloring_test() {
+    use crate::{
+        shape::{Line, Quad},
+        vector::Vector2,
+        Winding,
+    };
+
+    // Two quads each turning by 45 degrees meet at a 30 degree joint.
+    let direction = |degrees: f32| {
+        let radians = degrees.to_radians();
+        10.0 * Vector2::new(radians.cos(), radians.sin())
+    };
+    let start = Vector2::new(0.0, 0.0);
+    let ctrl = start + direction(0.0);
+    let joint = ctrl + direction(45.0);
+    let second_ctrl = joint + direction(75.0);
+    let end = second_ctrl + direction(120.0);
+    let contour = Contour::new(
+        vec![
+            Segment::Quad(Quad::new(start, ctrl, joint)),
+            Segment::Quad(Quad::new(joint, second_ctrl, end)),
+            Segment::Line(Line::new(end, start)),
+        ],
+        Winding(true),
+    );
+
+    let colors = color_contour(&contour, DEFAULT_ANGLE_THRESHOLD);
+    assert_ne!(colors[0], colors[1]);
+    assert_ne!(colors[1], colors[2]);
+    assert_ne!(colors[2], colors[0]);
+}
+
+#[test]
+fn segment_colors_test() {
+    let mut outline = crate::gen::rect_outline(16, 2.0);
+    let contour = &mut outline.shape.contours[0];
+    assert!(contour.segment_colors().is_empty());
+
+    contour.color_edges(DEFAULT_ANGLE_THRESHOLD);
+    assert_eq!(
+        contour.segment_colors(),
+        [
+            EdgeColor::Cyan,
+            EdgeColor::Magenta,
+            EdgeColor::Yellow,
+            EdgeColor::Magenta
+        ]
+    );
+
+    // Colors follow their segments when reversed.
+    contour.reverse();
+    assert_eq!(
+        contour.segment_colors(),
+        [
+            EdgeColor::Magenta,
+            EdgeColor::Yellow,
+            EdgeColor::Magenta,
+            EdgeColor::Cyan
+        ]
+    );
+}
+
+#[test]
+fn shape_coloring_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('O')
+        .build(crate::Scale::uniform(32.0), crate::Offset::uniform(2.0));
+    let contours = &outline.shape.contours;
+    assert_eq!(contours.len(), 2);
+
+    let colors = color_shape(&outline.shape, DEFAULT_ANGLE_THRESHOLD);
+    assert_eq!(colors[0].len(), contours[0].segments.len());
+    assert_eq!(colors[1].len(), contours[1].segments.len());
+    // Both contours of the `O` are smooth, which alone would make them
+    // both white.
+    assert!(color_contour(&contours[1], DEFAULT_ANGLE_THRESHOLD)
+        .iter()
+        .all(|c| *c == EdgeColor::White));
+
+    let (inner, outer, _) =
+        closest_segments(&contours[1], &contours[0]).unwrap();
+    assert_ne!(colors[1][inner], colors[0][outer]);
+
+    let mut shape = outline.shape;
+    shape.color_edges(DEFAULT_ANGLE_THRESHOLD);
+    assert_eq!(shape.contours[1].segment_colors(), colors[1]);
+
+    // A single contour is colored the same as by itself.
+    let rect = crate::gen::rect_outline(16, 2.0);
+    assert_eq!(
+        color_shape(&rect.shape, DEFAULT_ANGLE_THRESHOLD),
+        vec![color_contour(
+            &rect.shape.contours[0],
+            DEFAULT_ANGLE_THRESHOLD
+        )]
+    );
+}
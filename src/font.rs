@@ -2,7 +2,18 @@ use std::sync::Arc;
 
 use rusttype::{Font as RTFont, Glyph as RTGlyph, Rect, Scale, VMetrics};
 
-use crate::{gen::Bitmap, path::PathBuilder, shape::Shape, vector::Vector2};
+use owned_ttf_parser::{
+    gpos::{PairAdjustment, PositioningSubtable},
+    gsub::SubstitutionSubtable,
+    Face, GlyphId, OutlineBuilder, Tag,
+};
+
+use crate::{
+    gen::{Bitmap, GenError, DEFAULT_MAX_DIMENSION},
+    path::{FillConvention, FontUnitsBuilder, PathBuilder},
+    shape::Shape,
+    vector::{Affine2, Vector2},
+};
 
 pub struct Font<'a> {
     inner: Arc<RTFont<'a>>,
@@ -31,6 +42,138 @@ impl<'a> Font<'a> {
         self.inner.v_metrics_unscaled()
     }
 
+    /// Returns the height of capital letters above the baseline from the
+    /// `OS/2` table, scaled the same way as [`Font::v_metrics`].
+    pub fn cap_height(&self, scale: Scale) -> Option<f32> {
+        let face = self.face();
+        face.capital_height()
+            .map(|height| height as f32 * self.pixel_height_factor(scale))
+    }
+
+    /// Returns the height of lowercase letters like 'x' above the baseline
+    /// from the `OS/2` table, scaled the same way as [`Font::v_metrics`].
+    pub fn x_height(&self, scale: Scale) -> Option<f32> {
+        let face = self.face();
+        face.x_height()
+            .map(|height| height as f32 * self.pixel_height_factor(scale))
+    }
+
+    /// Returns the position and thickness of the underline from the `post`
+    /// table, scaled the same way as [`Font::v_metrics`].
+    ///
+    /// The position is the top of the underline relative to the baseline,
+    /// negative below it, the same as [`VMetrics::descent`].
+    pub fn underline(&self, scale: Scale) -> Option<(f32, f32)> {
+        let factor = self.pixel_height_factor(scale);
+        self.face().underline_metrics().map(|metrics| {
+            (
+                metrics.position as f32 * factor,
+                metrics.thickness as f32 * factor,
+            )
+        })
+    }
+
+    /// Returns the italic angle from the `post` table in degrees
+    /// counter-clockwise from the vertical, negative for fonts leaning to
+    /// the right. Fonts without the table are upright and return `0.0`.
+    ///
+    /// Pass it to [`Shape::deskew`] for upright outlines of italic glyphs.
+    pub fn italic_angle(&self) -> f32 {
+        self.face().italic_angle().unwrap_or(0.0)
+    }
+
+    /// Returns the smallest size in pixels per em from which the `gasp`
+    /// table asks for the same rendering as for all larger sizes.
+    ///
+    /// Fonts list special rendering behaviour for small sizes in the `gasp`
+    /// table. Sizes below the returned one are where the font author
+    /// expected the outlines to need help, so it is a sensible lower bound
+    /// for the [`Scale`] of generated fields. Returns [`None`] if the font
+    /// has no `gasp` table or the table treats all sizes the same.
+    pub fn recommended_ppem(&self) -> Option<u16> {
+        let data = self
+            .face()
+            .table_data(owned_ttf_parser::Tag::from_bytes(b"gasp"))?;
+        let read_u16 = |offset: usize| {
+            data.get(offset..offset + 2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+        // Each range record is the largest ppem it covers followed by the
+        // behaviour flags, sorted by the ppem.
+        let num_ranges = read_u16(2)? as usize;
+        if num_ranges < 2 {
+            return None;
+        }
+        let previous_max = read_u16(4 + (num_ranges - 2) * 4)?;
+        previous_max.checked_add(1)
+    }
+
+    /// Returns the format of the glyph outlines stored in the font, derived
+    /// from the tables present.
+    ///
+    /// Fields can be generated from both [`OutlineFormat::TrueType`] and
+    /// [`OutlineFormat::PostScript`] outlines, the latter made of cubic
+    /// curves.
+    pub fn outline_format(&self) -> OutlineFormat {
+        let tables = self.face().tables();
+        if tables.glyf.is_some() {
+            OutlineFormat::TrueType
+        } else if tables.cff.is_some() || tables.cff2.is_some() {
+            OutlineFormat::PostScript
+        } else if tables.cbdt.is_some() || tables.sbix.is_some() {
+            OutlineFormat::Bitmap
+        } else {
+            OutlineFormat::None
+        }
+    }
+
+    /// Checks if the font has a glyph for the provided character.
+    ///
+    /// Characters without a glyph are otherwise mapped to the `.notdef`
+    /// glyph with the id `0`.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.inner.glyph(c).id().0 != 0
+    }
+
+    /// Returns all characters with codepoints from `start` to `end`
+    /// (inclusive) which have a glyph in the font, ordered by codepoint.
+    ///
+    /// Useful for generating an atlas for a whole Unicode block, like
+    /// Cyrillic from `0x0400` to `0x04FF`.
+    pub fn chars_in_range(&self, start: u32, end: u32) -> Vec<char> {
+        (start..=end.min(char::MAX as u32))
+            .filter_map(char::from_u32)
+            .filter(|&c| self.has_glyph(c))
+            .collect()
+    }
+
+    /// Returns the `.notdef` glyph with the id `0`, which fonts usually draw
+    /// as an empty box (tofu) to mark missing characters.
+    pub fn notdef(&self) -> Glyph<'a> {
+        Glyph {
+            inner: self.inner.glyph(rusttype::GlyphId(0)),
+        }
+    }
+
+    /// Builds the outline of the glyph with the raw glyph id `gid` the same
+    /// way as [`Glyph::build`].
+    ///
+    /// Returns `None` if the id is not less than [`Font::glyph_count`].
+    pub fn outline_by_gid(
+        &self,
+        gid: u16,
+        scale: Scale,
+        offset: Offset,
+    ) -> Option<GlyphOutline> {
+        if gid as usize >= self.glyph_count() {
+            return None;
+        }
+        let glyph = Glyph {
+            inner: self.inner.glyph(rusttype::GlyphId(gid)),
+        };
+        Some(glyph.build(scale, offset))
+    }
+
     // TODO maybe use IntoGlyphId
     pub fn glyph<C: Into<char>>(&self, id: C) -> Glyph<'a> {
         let glyph = self.inner.glyph(id.into());
@@ -38,6 +181,435 @@ impl<'a> Font<'a> {
 
         Glyph { inner: glyph }
     }
+
+    /// Builds a shape of the `mark` glyph (like an accent) attached to the
+    /// `base` glyph using the mark to base anchors from the `GPOS` table.
+    ///
+    /// The shape is scaled the same way as in [`Glyph::build`] and placed
+    /// with the top left corner of the combined glyphs at the origin, so
+    /// [`Shape::bbox`] can be used to create a [`GlyphOutline`] from it.
+    ///
+    /// Returns `None` if any of the glyphs is missing or if the font doesn't
+    /// have anchors for attaching the mark to the base.
+    pub fn compose(
+        &self,
+        base: char,
+        mark: char,
+        scale: Scale,
+    ) -> Option<Shape> {
+        let face = self.face();
+        let base_id = face.glyph_index(base)?;
+        let mark_id = face.glyph_index(mark)?;
+        let (base_anchor, mark_anchor) =
+            mark_to_base_anchors(face, base_id, mark_id)?;
+        let base_box = face.glyph_bounding_box(base_id)?;
+        let mark_box = face.glyph_bounding_box(mark_id)?;
+
+        // Position of the mark relative to the base in font units.
+        let dx = base_anchor.0 as f32 - mark_anchor.0 as f32;
+        let dy = base_anchor.1 as f32 - mark_anchor.1 as f32;
+
+        // Same normalization as the one rusttype uses for the pixel height.
+        let height = face.ascender() as f32 - face.descender() as f32;
+        let factor = Vector2::new(scale.x / height, scale.y / height);
+
+        let left = (base_box.x_min as f32).min(mark_box.x_min as f32 + dx);
+        let top = (base_box.y_max as f32).max(mark_box.y_max as f32 + dy);
+        let origin =
+            Vector2::new(-(left * factor.x).floor(), (top * factor.y).ceil());
+        let mark_origin = origin + Vector2::new(dx * factor.x, -dy * factor.y);
+
+        let mut path = PathBuilder::new(Offset::uniform(0.0));
+        path.set_fill_convention(fill_convention(&self.inner));
+        face.outline_glyph(
+            base_id,
+            &mut FontUnitsBuilder::new(&mut path, factor, origin),
+        )?;
+        face.outline_glyph(
+            mark_id,
+            &mut FontUnitsBuilder::new(&mut path, factor, mark_origin),
+        )?;
+
+        Some(path.build_shape())
+    }
+
+    /// Generates the signed distance field of the glyph for `c` with every
+    /// step of the pipeline returning an error instead of panicking, for
+    /// rendering untrusted fonts.
+    ///
+    /// Fails with [`GenError::MissingGlyph`] if the font has no glyph for
+    /// the character and with [`GenError::InvalidScale`] for a zero or
+    /// non-finite scale. The glyph gets half of the `range` free on every
+    /// side. Malformed outlines fail with [`GenError::InvalidShape`].
+    pub fn try_generate_sdf(
+        &self,
+        c: char,
+        scale: Scale,
+        range: usize,
+    ) -> Result<Bitmap, GenError> {
+        if !self.has_glyph(c) {
+            return Err(GenError::MissingGlyph(c));
+        }
+        let valid = |s: f32| s.is_finite() && s != 0.0;
+        if !valid(scale.x) || !valid(scale.y) {
+            return Err(GenError::InvalidScale);
+        }
+        let offset = Offset::uniform((0.5 * range as f32).ceil());
+
+        self.glyph(c).try_build(scale, offset)?.generate_sdf(range)
+    }
+
+    /// Returns the width of the `text` laid out on one line, the sum of the
+    /// glyph advances adjusted by the kerning between neighbouring glyphs.
+    ///
+    /// Characters missing from the font are skipped, so they neither
+    /// advance nor kern with their neighbours.
+    pub fn measure(&self, text: &str, scale: Scale) -> f32 {
+        let text: String =
+            text.chars().filter(|&c| self.has_glyph(c)).collect();
+        self.layout(&text, scale)
+            .pop()
+            .map_or(0.0, |(glyph, pen)| pen + glyph.advance_width(scale))
+    }
+
+    /// Lays out the `text` on one line and returns every glyph with the
+    /// horizontal position of its origin on the baseline.
+    ///
+    /// Neighbouring glyphs are kerned and runs of characters forming a
+    /// standard ligature (like "fi") in the `GSUB` table are replaced with
+    /// the single ligature glyph. Characters missing from the font are laid
+    /// out as the [`Font::notdef`] glyph.
+    pub fn layout(&self, text: &str, scale: Scale) -> Vec<(Glyph<'a>, f32)> {
+        let face = self.face();
+        let lookups = ligature_lookups(face);
+        let ids: Vec<GlyphId> = text
+            .chars()
+            .map(|c| GlyphId(self.inner.glyph(c).id().0))
+            .collect();
+
+        let mut placed = Vec::with_capacity(ids.len());
+        let mut pen = 0.0;
+        let mut previous = None;
+        let mut i = 0;
+        while i < ids.len() {
+            let (id, count) =
+                gsub_ligature(face, &lookups, &ids[i..]).unwrap_or((ids[i], 1));
+            let id = rusttype::GlyphId(id.0);
+            if let Some(previous) = previous {
+                pen += self.pair_kerning(scale, previous, id);
+            }
+            let glyph = Glyph {
+                inner: self.inner.glyph(id),
+            };
+            let advance = glyph.advance_width(scale);
+            placed.push((glyph, pen));
+            pen += advance;
+            previous = Some(id);
+            i += count;
+        }
+
+        placed
+    }
+
+    /// Generates a single signed distance field of the whole `text` laid
+    /// out on one line, using the glyph advances and kerning.
+    ///
+    /// The glyphs are built into one shape, so overlapping glyphs merge
+    /// into a single filled area. The bitmap is tall enough for the ascent
+    /// and the descent and leaves half of the `range` free on every side.
+    ///
+    /// Fails with [`GenError::InvalidRange`] if the `range` is zero, even
+    /// if the `text` is only whitespace.
+    pub fn render_string_sdf(
+        &self,
+        text: &str,
+        scale: Scale,
+        range: usize,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::check_range(range)?;
+        let face = self.face();
+        let height = face.ascender() as f32 - face.descender() as f32;
+        let factor = Vector2::new(scale.x / height, scale.y / height);
+        let v_metrics = self.v_metrics(scale);
+
+        // Place the glyphs on the baseline and find the extent of the ink,
+        // which can reach outside of the advances and the line.
+        let mut placed = Vec::new();
+        let (mut left, mut right) = (0.0f32, 0.0f32);
+        let (mut top, mut bottom) = (v_metrics.ascent, v_metrics.descent);
+        for (glyph, pen) in self.layout(text, scale) {
+            let id = GlyphId(glyph.id());
+            if let Some(rect) = face.glyph_bounding_box(id) {
+                left = left.min(pen + rect.x_min as f32 * factor.x);
+                right = right.max(pen + rect.x_max as f32 * factor.x);
+                top = top.max(rect.y_max as f32 * factor.y);
+                bottom = bottom.min(rect.y_min as f32 * factor.y);
+            }
+            right = right.max(pen + glyph.advance_width(scale));
+            placed.push((id, pen));
+        }
+
+        let pad = (0.5 * range as f32).ceil();
+        let width = (right.ceil() - left.floor() + 2.0 * pad) as i32;
+        let height = (top.ceil() - bottom.floor() + 2.0 * pad) as i32;
+        let shift_x = pad - left.floor();
+        let baseline = pad + top.ceil();
+
+        let mut path = PathBuilder::new(Offset::uniform(0.0));
+        path.set_fill_convention(fill_convention(&self.inner));
+        for (id, pen) in placed {
+            let shift = Vector2::new(shift_x + pen, baseline);
+            // Whitespace has no outline and leaves the shape empty, which
+            // is generated as everything outside.
+            face.outline_glyph(
+                id,
+                &mut FontUnitsBuilder::new(&mut path, factor, shift),
+            );
+        }
+
+        let bbox = BBox::new(Vector2::ZERO_I32, Vector2::new(width, height));
+        GlyphOutline::from_shape(
+            path.build_shape(),
+            bbox,
+            scale,
+            Offset::uniform(0.0),
+        )
+        .generate_sdf(range)
+    }
+
+    /// Returns the factor converting font units to pixels, which makes the
+    /// distance between the ascent and the descent equal to `scale.y`.
+    #[inline]
+    fn pixel_height_factor(&self, scale: Scale) -> f32 {
+        self.inner.scale_for_pixel_height(scale.y)
+    }
+
+    /// Returns the underlying `ttf-parser` face for the tables rusttype
+    /// doesn't expose.
+    fn face(&self) -> &Face<'_> {
+        face(&self.inner)
+    }
+
+    /// Returns the kerning between two glyphs at the provided scale.
+    ///
+    /// Fonts without a `kern` table often kern in the pair adjustment
+    /// lookups of the `GPOS` table, which are used as a fallback.
+    fn pair_kerning(
+        &self,
+        scale: Scale,
+        left: rusttype::GlyphId,
+        right: rusttype::GlyphId,
+    ) -> f32 {
+        let kerning = self.inner.pair_kerning(scale, left, right);
+        if kerning != 0.0 {
+            return kerning;
+        }
+        let face = self.face();
+        let height = face.ascender() as f32 - face.descender() as f32;
+        gpos_pair_kerning(face, GlyphId(left.0), GlyphId(right.0))
+            .map_or(0.0, |units| units as f32 * scale.x / height)
+    }
+}
+
+/// Returns the parsed font face behind the rusttype font.
+fn face<'f>(font: &'f RTFont<'_>) -> &'f Face<'f> {
+    use owned_ttf_parser::AsFaceRef;
+    match font {
+        RTFont::Ref(face) => face,
+        RTFont::Owned(face) => face.as_face_ref(),
+    }
+}
+
+/// Format of the glyph outlines stored in a font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlineFormat {
+    /// Quadratic curves from the `glyf` table.
+    TrueType,
+    /// Cubic curves from the `CFF` or `CFF2` table.
+    PostScript,
+    /// Embedded images from the `CBDT` or `sbix` table, without outlines.
+    Bitmap,
+    /// The font has no glyph data.
+    None,
+}
+
+/// Returns the fill convention of the outlines stored in the font, which
+/// depends on the format of the outlines.
+fn fill_convention(font: &RTFont) -> FillConvention {
+    let tables = face(font).tables();
+    if tables.cff.is_some() || tables.cff2.is_some() {
+        FillConvention::PostScript
+    } else {
+        FillConvention::TrueType
+    }
+}
+
+/// Finds the horizontal advance adjustment of the first glyph of a pair in
+/// the pair adjustment lookups of the `GPOS` table.
+///
+/// Returns the adjustment in font units.
+fn gpos_pair_kerning(
+    face: &Face,
+    left: GlyphId,
+    right: GlyphId,
+) -> Option<i16> {
+    let gpos = face.tables().gpos?;
+    gpos.lookups
+        .into_iter()
+        .flat_map(|lookup| lookup.subtables.into_iter::<PositioningSubtable>())
+        .find_map(|subtable| match subtable {
+            PositioningSubtable::Pair(PairAdjustment::Format1 {
+                coverage,
+                sets,
+            }) => {
+                let index = coverage.get(left)?;
+                let (record, _) = sets.get(index)?.get(right)?;
+                Some(record.x_advance)
+            }
+            PositioningSubtable::Pair(PairAdjustment::Format2 {
+                coverage,
+                classes,
+                matrix,
+            }) => {
+                coverage.get(left)?;
+                let pair = (classes.0.get(left), classes.1.get(right));
+                let (record, _) = matrix.get(pair)?;
+                Some(record.x_advance)
+            }
+            _ => None,
+        })
+}
+
+/// Returns the indices of the lookups of the standard ligatures feature in
+/// the `GSUB` table, in the order they are applied.
+fn ligature_lookups(face: &Face) -> Vec<u16> {
+    let gsub = match face.tables().gsub {
+        Some(gsub) => gsub,
+        None => return Vec::new(),
+    };
+    let mut lookups: Vec<u16> = gsub
+        .features
+        .into_iter()
+        .filter(|feature| feature.tag == Tag::from_bytes(b"liga"))
+        .flat_map(|feature| feature.lookup_indices)
+        .collect();
+    lookups.sort_unstable();
+    lookups.dedup();
+
+    lookups
+}
+
+/// Finds the ligature substituting a run of glyphs at the start of
+/// `glyphs` in the provided ligature `lookups` of the `GSUB` table.
+///
+/// Returns the ligature glyph and the number of glyphs it replaces.
+fn gsub_ligature(
+    face: &Face,
+    lookups: &[u16],
+    glyphs: &[GlyphId],
+) -> Option<(GlyphId, usize)> {
+    let gsub = face.tables().gsub?;
+    let (&first, rest) = glyphs.split_first()?;
+    lookups
+        .iter()
+        .filter_map(|&index| gsub.lookups.get(index))
+        .flat_map(|lookup| lookup.subtables.into_iter::<SubstitutionSubtable>())
+        .find_map(|subtable| match subtable {
+            SubstitutionSubtable::Ligature(table) => {
+                let index = table.coverage.get(first)?;
+                // Ligatures in a set are ordered by preference.
+                table.ligature_sets.get(index)?.into_iter().find_map(
+                    |ligature| {
+                        let count = ligature.components.len() as usize;
+                        let matches = rest.len() >= count
+                            && ligature
+                                .components
+                                .into_iter()
+                                .zip(rest)
+                                .all(|(component, &glyph)| component == glyph);
+                        matches.then_some((ligature.glyph, count + 1))
+                    },
+                )
+            }
+            _ => None,
+        })
+}
+
+/// Finds the anchors attaching the mark glyph to the base glyph in the
+/// mark to base lookups of the `GPOS` table.
+///
+/// Returns the base anchor and the mark anchor in font units.
+fn mark_to_base_anchors(
+    face: &Face,
+    base: GlyphId,
+    mark: GlyphId,
+) -> Option<((i16, i16), (i16, i16))> {
+    let gpos = face.tables().gpos?;
+    gpos.lookups
+        .into_iter()
+        .flat_map(|lookup| lookup.subtables.into_iter::<PositioningSubtable>())
+        .find_map(|subtable| match subtable {
+            PositioningSubtable::MarkToBase(table) => {
+                let mark_index = table.mark_coverage.get(mark)?;
+                let base_index = table.base_coverage.get(base)?;
+                let (class, mark_anchor) = table.marks.get(mark_index)?;
+                let base_anchor = table.anchors.get(base_index, class)?;
+                Some((
+                    (base_anchor.x, base_anchor.y),
+                    (mark_anchor.x, mark_anchor.y),
+                ))
+            }
+            _ => None,
+        })
+}
+
+/// An ordered list of fonts where every font acts as a fallback for the
+/// fonts before it.
+///
+/// Useful when a single font doesn't cover all of the needed scripts.
+pub struct FontStack<'a> {
+    fonts: Vec<Font<'a>>,
+}
+
+impl<'a> FontStack<'a> {
+    pub fn new(fonts: Vec<Font<'a>>) -> Self {
+        Self { fonts }
+    }
+
+    /// Adds a font with the lowest priority.
+    pub fn push(&mut self, font: Font<'a>) {
+        self.fonts.push(font);
+    }
+
+    #[inline]
+    pub fn fonts(&self) -> &[Font<'a>] {
+        &self.fonts
+    }
+
+    /// Returns the glyph from the first font which contains the provided
+    /// character together with the index of that font in the stack.
+    ///
+    /// Returns `None` if none of the fonts contain the character.
+    pub fn glyph(&self, c: char) -> Option<(Glyph<'a>, usize)> {
+        self.fonts
+            .iter()
+            .position(|font| font.has_glyph(c))
+            .map(|index| (self.fonts[index].glyph(c), index))
+    }
+
+    /// Same as [`FontStack::glyph`] but falls back to the `.notdef` glyph
+    /// of the first font if none of the fonts contain the character.
+    ///
+    /// Panics if the stack is empty.
+    pub fn glyph_or_notdef(&self, c: char) -> (Glyph<'a>, usize) {
+        self.glyph(c).unwrap_or_else(|| {
+            let font = self
+                .fonts
+                .first()
+                .expect("FontStack Error: There are no fonts in the stack!");
+            (font.notdef(), 0)
+        })
+    }
 }
 
 pub struct Glyph<'font> {
@@ -50,6 +622,26 @@ impl Glyph<'_> {
         self.inner.id().0
     }
 
+    /// Feeds the raw outline of the glyph to the provided `builder`,
+    /// bypassing the [`ShapeBuilder`](crate::ShapeBuilder), and returns the
+    /// bounding box of the glyph or [`None`] if the glyph has no outline.
+    ///
+    /// Coordinates are unscaled font units with the y axis pointing up,
+    /// exactly as stored in the font. Useful for plugging in an existing
+    /// tessellator while still loading the fonts through this crate.
+    pub fn outline_with(
+        &self,
+        builder: &mut dyn OutlineBuilder,
+    ) -> Option<owned_ttf_parser::Rect> {
+        let id = GlyphId(self.inner.id().0);
+        face(self.inner.font()).outline_glyph(id, builder)
+    }
+
+    /// Returns the horizontal advance of the glyph at the provided scale.
+    pub fn advance_width(&self, scale: Scale) -> f32 {
+        self.inner.clone().scaled(scale).h_metrics().advance_width
+    }
+
     /// Builds a [`GlyphOutline`] with the provided [`Scale`] and [`Offset`].
     ///
     /// Scale is automatically normalized by the `units_per_em` factor.
@@ -57,56 +649,460 @@ impl Glyph<'_> {
     /// Offset is mainly used in the sdf generation process for better view
     /// of the glyph. It adds empty space to the left, right, top or bottom
     /// of the outline .
+    ///
+    /// The horizontal and vertical scale are independent, so a glyph can be
+    /// condensed or expanded by building it with a smaller or larger
+    /// `scale.x` than `scale.y`, while [`Scale::uniform`] keeps the
+    /// proportions of the font.
+    ///
+    /// A negative scale mirrors the glyph along that axis. The contours are
+    /// reversed when mirrored along a single axis so the inside of the glyph
+    /// keeps a positive distance.
+    ///
+    /// Panics if the glyph at the provided scale doesn't fit into the pixel
+    /// coordinates, see [`Glyph::try_build`].
     pub fn build(self, scale: Scale, offset: Offset) -> GlyphOutline {
+        self.try_build(scale, offset)
+            .expect("Glyph Error: The glyph is too large to be built!")
+    }
+
+    /// Same as [`Glyph::build`] but returns [`GenError::TooLarge`] when the
+    /// scaled glyph can't be represented in `i32` pixel coordinates.
+    pub fn try_build(
+        self,
+        scale: Scale,
+        offset: Offset,
+    ) -> Result<GlyphOutline, GenError> {
         // Offset the shape to the right and the bottom
         let pos = rusttype::Point {
             x: offset.x,
             y: offset.y,
         };
-        let glyph = self.inner.scaled(scale).positioned(pos);
+        // rusttype doesn't expect negative scales so the glyph is built
+        // unmirrored and mirrored afterwards.
+        let unmirrored = Scale {
+            x: scale.x.abs(),
+            y: scale.y.abs(),
+        };
+        let scaled = self.inner.scaled(unmirrored);
+
+        // rusttype rounds the bounds to `i32` without checking for overflows,
+        // so the exact bounds are checked first. Half of the range is left
+        // for the width and the height.
+        if let Some(rect) = scaled.exact_bounding_box() {
+            let limit = (i32::MAX / 2) as f32;
+            let corners = [
+                rect.min.x + pos.x,
+                rect.min.y + pos.y,
+                rect.max.x + pos.x,
+                rect.max.y + pos.y,
+            ];
+            if !corners.iter().all(|c| c.abs() < limit) {
+                return Err(GenError::TooLarge {
+                    width: (rect.width() + offset.x * 2.0) as u64,
+                    height: (rect.height() + offset.y * 2.0) as u64,
+                });
+            }
+        }
+
+        let fill = fill_convention(scaled.font());
+        let glyph = scaled.positioned(pos);
         let mut builder = PathBuilder::new(offset);
+        builder.set_fill_convention(fill);
 
-        let bbox = BBox::from(glyph.pixel_bounding_box().unwrap());
-        dbg!(bbox);
+        let bbox = match glyph.pixel_bounding_box() {
+            Some(rect) => BBox::from(rect),
+            // Glyphs without an outline, like a space, are empty.
+            None => {
+                let bbox = BBox::new(Vector2::ZERO_I32, Vector2::ZERO_I32);
+                let shape = Shape::new(Vec::new());
+                return Ok(GlyphOutline::from_shape(
+                    shape, bbox, scale, offset,
+                ));
+            }
+        };
 
-        let result = glyph.build_outline(&mut builder);
-        assert!(result, "Glyph outline error!");
+        if !glyph.build_outline(&mut builder) {
+            return Err(GenError::InvalidShape);
+        }
 
-        let shape = builder.build_shape();
+        // The outline is placed relative to the top left corner of the
+        // pixel bounding box, which was moved by the position.
+        let mut origin = Vector2::new(
+            pos.x - bbox.tl.x as f32 + offset.x,
+            pos.y - bbox.tl.y as f32 + offset.y,
+        );
 
-        GlyphOutline::from_shape(shape, bbox, offset)
+        let mut shape = builder.build_shape();
+        if scale.x < 0.0 || scale.y < 0.0 {
+            // Mirrored inside of the generated tile.
+            let (pad_x, pad_y) = offset.padding();
+            let size = Vector2::new(
+                bbox.width().saturating_add(pad_x as i32 * 2) as f32,
+                bbox.height().saturating_add(pad_y as i32 * 2) as f32,
+            );
+            shape.mirror(scale.x < 0.0, scale.y < 0.0, size);
+            if scale.x < 0.0 {
+                origin.x = size.x - origin.x;
+            }
+            if scale.y < 0.0 {
+                origin.y = size.y - origin.y;
+            }
+        }
+        // Holes of broken fonts winding all contours the same way would be
+        // filled otherwise.
+        shape.normalize_windings();
+
+        let mut outline = GlyphOutline::from_shape(shape, bbox, scale, offset);
+        outline.origin = origin;
+        Ok(outline)
+    }
+
+    /// Builds the glyph mapped through an affine `transform`, which allows
+    /// rotated, skewed or mirrored glyphs. The `offset` is the same as in
+    /// [`Glyph::build`].
+    ///
+    /// The font units are normalized the same way as for [`Scale`], with
+    /// the y axis pointing down, so [`Affine2::scale`] with the values of a
+    /// [`Scale`] gives the same outline as [`Glyph::build`]. The
+    /// translation of the transform only changes the subpixel position
+    /// since the glyph is placed into the tile by its bounding box.
+    ///
+    /// Panics if the transformed glyph doesn't fit into the pixel
+    /// coordinates, see [`Glyph::try_build_with_transform`].
+    pub fn build_with_transform(
+        &self,
+        transform: &Affine2,
+        offset: Offset,
+    ) -> GlyphOutline {
+        self.try_build_with_transform(transform, offset)
+            .expect("Glyph Error: The glyph is too large to be built!")
+    }
+
+    /// Same as [`Glyph::build_with_transform`] but returns
+    /// [`GenError::TooLarge`] when the transformed glyph can't be
+    /// represented in `i32` pixel coordinates.
+    ///
+    /// Fails with [`GenError::InvalidShape`] if the font has a bounding box
+    /// for the glyph but its outline can't be read, the same as
+    /// [`Glyph::try_build`].
+    pub fn try_build_with_transform(
+        &self,
+        transform: &Affine2,
+        offset: Offset,
+    ) -> Result<GlyphOutline, GenError> {
+        let font = self.inner.font();
+        let face = face(font);
+        let id = GlyphId(self.inner.id().0);
+        // Lengths of the transformed axes, reported as the scale of the
+        // outline.
+        let scale = Scale {
+            x: transform.xx.hypot(transform.yx),
+            y: transform.xy.hypot(transform.yy),
+        };
+
+        // Same normalization as the one rusttype uses for the pixel height.
+        let height = face.ascender() as f32 - face.descender() as f32;
+        let transform =
+            Affine2::scale(1.0 / height, 1.0 / height).then(*transform);
+
+        let rect = match face.glyph_bounding_box(id) {
+            Some(rect) => rect,
+            // Glyphs without an outline, like a space, are empty.
+            None => {
+                let bbox = BBox::new(Vector2::ZERO_I32, Vector2::ZERO_I32);
+                let shape = Shape::new(Vec::new());
+                return Ok(GlyphOutline::from_shape(
+                    shape, bbox, scale, offset,
+                ));
+            }
+        };
+        let corners = [
+            (rect.x_min, rect.y_min),
+            (rect.x_max, rect.y_min),
+            (rect.x_min, rect.y_max),
+            (rect.x_max, rect.y_max),
+        ]
+        .map(|(x, y)| transform.transform(Vector2::new(x as f32, -y as f32)));
+        let min = corners.iter().fold(corners[0], |min, c| {
+            Vector2::new(min.x.min(c.x), min.y.min(c.y))
+        });
+        let max = corners.iter().fold(corners[0], |max, c| {
+            Vector2::new(max.x.max(c.x), max.y.max(c.y))
+        });
+
+        // The glyph is positioned at the offset, the same as in
+        // `Glyph::try_build`.
+        let position = Vector2::new(offset.x, offset.y);
+        let (min, max) = (min + position, max + position);
+        let limit = (i32::MAX / 2) as f32;
+        if ![min.x, min.y, max.x, max.y].iter().all(|c| c.abs() < limit) {
+            return Err(GenError::TooLarge {
+                width: (max.x - min.x + offset.x * 2.0) as u64,
+                height: (max.y - min.y + offset.y * 2.0) as u64,
+            });
+        }
+        let bbox = BBox::new(
+            Vector2::new(min.x.floor() as i32, min.y.floor() as i32),
+            Vector2::new(max.x.ceil() as i32, max.y.ceil() as i32),
+        );
+
+        // The outline is placed relative to the top left corner of the
+        // pixel bounding box, which was moved by the position.
+        let transform = transform.then(Affine2::translation(
+            2.0 * offset.x - bbox.tl.x as f32,
+            2.0 * offset.y - bbox.tl.y as f32,
+        ));
+        let mut path = PathBuilder::new(Offset::uniform(0.0));
+        path.set_fill_convention(fill_convention(font));
+        let mut builder =
+            FontUnitsBuilder::with_transform(&mut path, transform);
+        if face.outline_glyph(id, &mut builder).is_none() {
+            return Err(GenError::InvalidShape);
+        }
+
+        let mut shape = path.build_shape();
+        // Mirroring transforms reverse the direction of the contours.
+        shape.normalize_windings();
+
+        let mut outline = GlyphOutline::from_shape(shape, bbox, scale, offset);
+        outline.origin = transform.transform(Vector2::ZERO_F32);
+        Ok(outline)
     }
 }
 
 pub struct GlyphOutline {
     pub(crate) bbox: BBox,
     pub(crate) shape: Shape,
+    pub(crate) scale: Scale,
     pub(crate) offset: Offset,
+    pub(crate) max_dimension: u32,
+    /// Position of the glyph origin on the baseline in shape coordinates.
+    pub(crate) origin: Vector2<f32>,
 }
 
 impl GlyphOutline {
-    /// Initialize a new [`GlyphOutline`] with the provided shape and it's
-    /// bounding box.
+    /// Initialize a new [`GlyphOutline`] with the provided shape, it's
+    /// bounding box and the scale which was used to build the shape.
     ///
     /// Use the [`Self::generate`] functions to create a distance field bitmap.
-    pub fn from_shape(shape: Shape, bbox: BBox, offset: Offset) -> Self {
+    ///
+    /// Generated bitmaps are limited to [`DEFAULT_MAX_DIMENSION`] pixels in
+    /// each direction, see [`Self::set_max_dimension`].
+    pub fn from_shape(
+        shape: Shape,
+        bbox: BBox,
+        scale: Scale,
+        offset: Offset,
+    ) -> Self {
         Self {
             bbox,
             shape,
+            scale,
             offset,
+            max_dimension: DEFAULT_MAX_DIMENSION,
+            origin: Vector2::new(offset.x, offset.y),
         }
     }
 
+    /// Sets the largest width or height in pixels a generated bitmap can
+    /// have. Generating a larger bitmap fails with [`GenError::TooLarge`]
+    /// before any memory is allocated.
+    ///
+    /// Useful when the scale comes from an untrusted source.
+    #[inline]
+    pub fn set_max_dimension(&mut self, max_dimension: u32) {
+        self.max_dimension = max_dimension;
+    }
+
+    #[inline]
+    pub fn max_dimension(&self) -> u32 {
+        self.max_dimension
+    }
+
     /// Returns a image bitmap with signed distance fields.
-    pub fn generate_sdf(&self, range: usize) -> Bitmap {
+    pub fn generate_sdf(&self, range: usize) -> Result<Bitmap, GenError> {
         crate::gen::gen_sdf(self, range)
     }
 
+    /// Same as [`GlyphOutline::generate_sdf`] but the bitmap carries the
+    /// windings of the contours, see [`Bitmap::debug`].
+    pub fn generate_sdf_debug(&self, range: usize) -> Result<Bitmap, GenError> {
+        crate::gen::gen_sdf_debug(self, range)
+    }
+
+    /// Same as [`GlyphOutline::generate_sdf`] but the sampling grid is
+    /// moved by a fractional `offset` in pixels, which can be used to cache
+    /// a few subpixel positions of small glyphs.
+    ///
+    /// A positive offset moves the sampling to the right and down, moving
+    /// the shape in the bitmap to the left and up.
+    pub fn generate_sdf_offset(
+        &self,
+        range: usize,
+        offset: Vector2<f32>,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::gen_sdf_shifted(self, range, offset)
+    }
+
+    /// Returns the largest distance from the edge to a point inside of the
+    /// glyph in pixels, half of the thickness of its thickest stroke.
+    ///
+    /// A `range` above twice this distance never clamps the inside of the
+    /// glyph. Sampled at pixel centers, so it can be up to half a pixel
+    /// diagonal lower than the exact value.
+    pub fn max_interior_distance(&self) -> f32 {
+        crate::gen::max_interior_distance(self)
+    }
+
+    /// Same as [`GlyphOutline::generate_sdf`] but also returns a hard
+    /// coverage mask of the glyph, derived from the same distances.
+    pub fn generate_sdf_with_mask(
+        &self,
+        range: usize,
+    ) -> Result<(Bitmap, Bitmap), GenError> {
+        crate::gen::gen_sdf_with_mask(self, range)
+    }
+
+    /// Same as [`GlyphOutline::generate_sdf`] but every pixel is sampled
+    /// at a point randomly moved by up to `amount` pixels, deterministic
+    /// for the `seed`. Useful for testing the stability of the field.
+    pub fn generate_sdf_jittered(
+        &self,
+        range: usize,
+        seed: u64,
+        amount: f32,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::gen_sdf_jittered(self, range, seed, amount)
+    }
+
+    /// Same as [`GlyphOutline::generate_sdf`] but the distances aren't
+    /// clamped to the `range`, so the true distance is stored everywhere.
+    pub fn generate_sdf_unclamped(
+        &self,
+        range: usize,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::gen_sdf_unclamped(self, range)
+    }
+
+    /// Returns the same bitmap as [`GlyphOutline::generate_sdf`], but
+    /// computes the distances on a coarse grid of samples `step` pixels
+    /// apart first and refines only the cells close to the edge at the full
+    /// resolution, which is faster for large bitmaps.
+    pub fn generate_sdf_coarse_to_fine(
+        &self,
+        range: usize,
+        step: u32,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::gen_sdf_coarse_to_fine(self, range, step)
+    }
+
     /// Returns a image bitmap with pseudo signed distance fields.
-    pub fn generate_pseudo_sdf(&self, range: usize) -> Bitmap {
+    pub fn generate_pseudo_sdf(
+        &self,
+        range: usize,
+    ) -> Result<Bitmap, GenError> {
         crate::gen::gen_pseudo_sdf(self, range)
     }
 
+    /// Returns a signed distance field with antialiased edges baked in,
+    /// where the distances are put through a smoothstep `smoothing` pixels
+    /// wide on both sides of the edge.
+    ///
+    /// [`Bitmap::normalized_distances`] then returns the coverage of
+    /// every pixel, saturating at `0.0` outside and `1.0` inside.
+    pub fn generate_hybrid(
+        &self,
+        range: usize,
+        smoothing: f32,
+    ) -> Result<Bitmap, GenError> {
+        crate::gen::gen_hybrid(self, range, smoothing)
+    }
+
+    /// Returns an RGB bitmap where every pixel is tinted by the color of the
+    /// closest edge, meant for debugging the edge coloring.
+    pub fn generate_edge_color_map(&self) -> Result<Bitmap, GenError> {
+        crate::gen::gen_edge_color_map(self)
+    }
+
+    /// Returns the scale the outline was built with.
+    #[inline]
+    pub fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    /// Returns the bounding box of the outline, without the offset.
+    #[inline]
+    pub fn bbox(&self) -> BBox {
+        self.bbox
+    }
+
+    /// Returns points placed evenly along the outline, roughly `spacing`
+    /// pixels apart, see [`Shape::sample_outline`].
+    pub fn sample_outline(&self, spacing: f32) -> Vec<Vector2<f32>> {
+        self.shape.sample_outline(spacing)
+    }
+
+    /// Returns how far below the top of the generated tile the baseline
+    /// sits, in pixels.
+    ///
+    /// A renderer places the tile this far above the baseline of the line,
+    /// which is `ascent` below the top of the line, see [`VMetrics`]. The
+    /// rest of the tile below the baseline holds the descender and the
+    /// offset.
+    #[inline]
+    pub fn baseline_offset(&self) -> f32 {
+        self.origin.y
+    }
+
+    /// Returns the tight bounds of the outline relative to the glyph origin
+    /// on the baseline, rounded outwards to whole pixels.
+    ///
+    /// Unlike the advance, which only tells where the next glyph starts,
+    /// the ink bounds cover everything that is drawn. A glyph overhangs
+    /// its advance when the right edge is further than the advance width
+    /// or when the left edge is negative.
+    pub fn ink_bounds(&self) -> BBox {
+        if self.shape.segment_count() == 0 {
+            return BBox::new(Vector2::ZERO_I32, Vector2::ZERO_I32);
+        }
+        let (min, max) = self.shape.bounds();
+        BBox {
+            tl: Vector2::new(
+                (min.x - self.origin.x).floor() as i32,
+                (min.y - self.origin.y).floor() as i32,
+            ),
+            br: Vector2::new(
+                (max.x - self.origin.x).ceil() as i32,
+                (max.y - self.origin.y).ceil() as i32,
+            ),
+        }
+    }
+
+    /// Returns the pixels of the generated bitmap a signed distance field
+    /// with the provided `range` can reach, as the `x` and `y` of the top
+    /// left corner followed by the width and the height.
+    ///
+    /// The rectangle is the tight bounds of the shape grown by half of the
+    /// range on every side and rounded outwards to whole pixels. Pixels
+    /// outside of it are always as far outside as the range allows.
+    pub fn raster_bounds(&self, range: usize) -> (i32, i32, u32, u32) {
+        if self.shape.segment_count() == 0 {
+            return (0, 0, 0, 0);
+        }
+        let half_range = 0.5 * range as f32;
+        let (min, max) = self.shape.bounds();
+        let (x, y) = (
+            (min.x - half_range).floor() as i32,
+            (min.y - half_range).floor() as i32,
+        );
+        let (right, bottom) = (
+            (max.x + half_range).ceil() as i32,
+            (max.y + half_range).ceil() as i32,
+        );
+        (x, y, right.abs_diff(x), bottom.abs_diff(y))
+    }
+
     /// Returns the width of the shape's bounding box.
     #[inline]
     pub fn width(&self) -> i32 {
@@ -127,7 +1123,7 @@ impl GlyphOutline {
 ///
 /// [`BBox`] implies that the uv coordinate system is used meaning
 /// y coordinate increases downwards.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BBox {
     /// Top left point.
     pub tl: Vector2<i32>,
@@ -140,23 +1136,49 @@ impl BBox {
         Self { tl, br }
     }
 
+    /// Scales the bounding box while keeping it conservative, meaning the
+    /// top left point is rounded down and the bottom right point is
+    /// rounded up.
+    ///
+    /// Coordinates which don't fit into `i32` are saturated.
     pub fn scale(&mut self, scale: Scale) {
-        self.tl.x *= scale.x.ceil() as i32;
-        self.tl.y *= scale.y.ceil() as i32;
-        self.br.x *= scale.x.ceil() as i32;
-        self.br.y *= scale.y.ceil() as i32;
+        self.tl.x = (self.tl.x as f32 * scale.x).floor() as i32;
+        self.tl.y = (self.tl.y as f32 * scale.y).floor() as i32;
+        self.br.x = (self.br.x as f32 * scale.x).ceil() as i32;
+        self.br.y = (self.br.y as f32 * scale.y).ceil() as i32;
+    }
+
+    /// Creates a bounding box from a glyph bounding box in font units
+    /// scaled by the provided [`Scale`].
+    ///
+    /// Gives the same result as [`BBox::from`] followed by [`BBox::scale`].
+    pub fn from_rect_scaled(
+        rect: owned_ttf_parser::Rect,
+        scale: Scale,
+    ) -> Self {
+        // Font units have the y coordinate increasing upwards.
+        let tl = Vector2::new(
+            (rect.x_min as f32 * scale.x).floor() as i32,
+            (-(rect.y_max as f32) * scale.y).floor() as i32,
+        );
+        let br = Vector2::new(
+            (rect.x_max as f32 * scale.x).ceil() as i32,
+            (-(rect.y_min as f32) * scale.y).ceil() as i32,
+        );
+        Self { tl, br }
     }
 
+    /// Returns the width, saturated to `i32::MAX` if the box is wider.
     #[inline]
     pub fn width(&self) -> i32 {
-        self.br.x - self.tl.x
+        self.br.x.saturating_sub(self.tl.x)
     }
 
-    // TODO maybe fix
+    /// Returns the height, saturated to `i32::MAX` if the box is taller.
     #[inline]
     pub fn height(&self) -> i32 {
         // y increases downwards
-        self.br.y - self.tl.y
+        self.br.y.saturating_sub(self.tl.y)
     }
 }
 
@@ -169,6 +1191,16 @@ impl From<Rect<i32>> for BBox {
     }
 }
 
+impl From<owned_ttf_parser::Rect> for BBox {
+    fn from(rect: owned_ttf_parser::Rect) -> Self {
+        // Font units have the y coordinate increasing upwards.
+        BBox {
+            tl: Vector2::new(rect.x_min as i32, -(rect.y_max as i32)),
+            br: Vector2::new(rect.x_max as i32, -(rect.y_min as i32)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Offset {
     pub x: f32,
@@ -185,6 +1217,637 @@ impl Offset {
             y: offset,
         }
     }
+
+    /// Returns the whole pixels added to both sides of the generated
+    /// bitmap horizontally and vertically.
+    ///
+    /// Fractional offsets are rounded up, since the outline is moved by the
+    /// exact offset and rounding down would leave less space than asked for
+    /// on the right and the bottom.
+    pub(crate) fn padding(&self) -> (u32, u32) {
+        // Negative offsets saturate to no padding.
+        (self.x.ceil() as u32, self.y.ceil() as u32)
+    }
+}
+
+#[test]
+fn bbox_scaled_rect_test() {
+    let rect = owned_ttf_parser::Rect {
+        x_min: -12,
+        y_min: -431,
+        x_max: 1013,
+        y_max: 1456,
+    };
+
+    for scale in [0.013, 0.5, 1.0, 2.75, 3.0] {
+        let scale = Scale::uniform(scale);
+        let mut two_step = BBox::from(rect);
+        two_step.scale(scale);
+
+        assert_eq!(BBox::from_rect_scaled(rect, scale), two_step);
+    }
+
+    // The extremes of font units can't be negated as `i16`.
+    let rect = owned_ttf_parser::Rect {
+        x_min: i16::MIN,
+        y_min: i16::MIN,
+        x_max: i16::MAX,
+        y_max: i16::MAX,
+    };
+    let bbox = BBox::from_rect_scaled(rect, Scale::uniform(1.0));
+    assert_eq!(bbox, BBox::from(rect));
+    assert_eq!(bbox.br.y, 32768);
+}
+
+#[test]
+fn outline_scale_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale { x: 37.5, y: 41.0 };
+    let outline = font.glyph('g').build(scale, Offset::uniform(3.0));
+
+    assert_eq!(outline.scale(), scale);
+    assert_eq!(outline.bbox().width(), outline.width());
+    assert_eq!(outline.bbox().height(), outline.height());
+}
+
+#[test]
+fn font_stack_test() {
+    let latin =
+        Font::from_slice(include_bytes!("../examples/fonts/monserat.ttf"));
+    let fallback = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let stack = FontStack::new(vec![latin, fallback]);
+
+    // Both fonts have Latin letters so the first one is used.
+    assert_eq!(stack.glyph('A').unwrap().1, 0);
+    // Only the first font has this one.
+    assert_eq!(stack.glyph('Ǎ').unwrap().1, 0);
+    // Greek is only covered by the fallback.
+    let (glyph, index) = stack.glyph('λ').unwrap();
+    assert_eq!(index, 1);
+    assert_ne!(glyph.id(), 0);
+    assert!(stack.glyph('\u{10FFFD}').is_none());
+}
+
+#[test]
+fn mirrored_glyph_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = Offset::uniform(2.0);
+    let sdf = font
+        .glyph('L')
+        .build(Scale::uniform(32.0), offset)
+        .generate_sdf(6)
+        .unwrap();
+    let mirrored = font
+        .glyph('L')
+        .build(Scale { x: -32.0, y: 32.0 }, offset)
+        .generate_sdf(6)
+        .unwrap();
+
+    assert_eq!((mirrored.width, mirrored.height), (sdf.width, sdf.height));
+    for y in 0..sdf.height {
+        for x in 0..sdf.width {
+            let expected = sdf.distance(sdf.width - 1 - x, y);
+            assert!((mirrored.distance(x, y) - expected).abs() < 1e-3);
+        }
+    }
+    // The stem of the mirrored 'L' is on the right and has a positive
+    // distance.
+    let stem_x = (0..sdf.width).find(|&x| sdf.distance(x, 10) > 0.0).unwrap();
+    assert!(mirrored.distance(sdf.width - 1 - stem_x, 10) > 0.0);
+}
+
+#[test]
+fn recommended_ppem_test() {
+    let roboto = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    assert_eq!(roboto.recommended_ppem(), Some(9));
+
+    // Only a single range covering every size.
+    let montserrat =
+        Font::from_slice(include_bytes!("../examples/fonts/monserat.ttf"));
+    assert_eq!(montserrat.recommended_ppem(), None);
+
+    // No gasp table at all.
+    let cantarell =
+        Font::from_slice(include_bytes!("../examples/fonts/Cantarell-VF.otf"));
+    assert_eq!(cantarell.recommended_ppem(), None);
+}
+
+#[test]
+fn condensed_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = Offset::uniform(2.0);
+    let regular = font.glyph('H').build(Scale::uniform(64.0), offset);
+    let condensed = font.glyph('H').build(Scale { x: 32.0, y: 64.0 }, offset);
+
+    let (regular, condensed) = (regular.ink_bounds(), condensed.ink_bounds());
+    assert!((condensed.width() * 2 - regular.width()).abs() <= 2);
+    assert_eq!(condensed.height(), regular.height());
+}
+
+#[test]
+fn raster_bounds_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('g')
+        .build(Scale::uniform(40.0), Offset::uniform(4.0));
+    let range = 6;
+    let (x, y, w, h) = outline.raster_bounds(range);
+    let (min, max) = outline.shape.bounds();
+
+    // The bounds enclose the shape with the range margin, but not more than
+    // a pixel further.
+    let half_range = 0.5 * range as f32;
+    let (left, top) = (x as f32, y as f32);
+    let (right, bottom) = (left + w as f32, top + h as f32);
+    assert!(left <= min.x - half_range && left > min.x - half_range - 1.0);
+    assert!(top <= min.y - half_range && top > min.y - half_range - 1.0);
+    assert!(right >= max.x + half_range && right < max.x + half_range + 1.0);
+    assert!(bottom >= max.y + half_range && bottom < max.y + half_range + 1.0);
+
+    // Everything outside of the bounds is far outside.
+    let sdf = outline.generate_sdf(range).unwrap();
+    for py in 0..sdf.height {
+        for px in 0..sdf.width {
+            let (px_i, py_i) = (px as i32, py as i32);
+            if px_i < x
+                || py_i < y
+                || px_i >= right as i32
+                || py_i >= bottom as i32
+            {
+                assert_eq!(sdf.distance(px, py), -half_range);
+            }
+        }
+    }
+}
+
+#[test]
+fn baseline_offset_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale::uniform(32.0);
+    let offset = 3.0;
+
+    // `H` sits on the baseline, so only the offset is below it.
+    let h = font.glyph('H').build(scale, Offset::uniform(offset));
+    let tile = h.generate_sdf(4).unwrap();
+    assert!((h.baseline_offset() + offset - tile.height as f32).abs() < 1e-4);
+    assert_eq!(h.baseline_offset(), offset + h.height() as f32);
+
+    // The descender of `p` takes the rest of the tile.
+    let p = font.glyph('p').build(scale, Offset::uniform(offset));
+    let tile = p.generate_sdf(4).unwrap();
+    let descent = p.ink_bounds().br.y as f32 + offset;
+    assert!(p.ink_bounds().br.y > 0);
+    assert!((p.baseline_offset() + descent - tile.height as f32).abs() <= 1.0);
+}
+
+#[test]
+fn ink_bounds_test() {
+    let font =
+        Font::from_slice(include_bytes!("../examples/fonts/monserat.ttf"));
+    let scale = Scale::uniform(64.0);
+
+    // Montserrat's 'f' reaches past its advance.
+    let f = font.glyph('f');
+    let advance = f.advance_width(scale);
+    let ink = f.build(scale, Offset::uniform(3.0)).ink_bounds();
+    assert!(ink.br.x as f32 > advance);
+    assert!(ink.tl.x >= 0);
+    assert!(ink.tl.y < 0 && ink.br.y <= 0);
+
+    // The tail of 'j' goes left of the origin and below the baseline.
+    let ink = font
+        .glyph('j')
+        .build(scale, Offset::uniform(3.0))
+        .ink_bounds();
+    assert!(ink.tl.x < 0);
+    assert!(ink.br.y > 0);
+
+    // Regular glyphs stay within their advance.
+    let n = font.glyph('n');
+    let advance = n.advance_width(scale);
+    let ink = n.build(scale, Offset::uniform(0.0)).ink_bounds();
+    assert!(ink.tl.x >= 0 && (ink.br.x as f32) < advance);
+}
+
+#[test]
+fn typo_heights_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let unscaled = font.v_metrics_unscaled();
+    let scale = Scale::uniform(unscaled.ascent - unscaled.descent);
+
+    // Scaled to the font units.
+    assert!((font.cap_height(scale).unwrap() - 1456.0).abs() < 1e-2);
+    assert!((font.x_height(scale).unwrap() - 1082.0).abs() < 1e-2);
+
+    let scale = Scale::uniform(32.0);
+    let v_metrics = font.v_metrics(scale);
+    let cap_height = font.cap_height(scale).unwrap();
+    assert!(cap_height > font.x_height(scale).unwrap());
+    assert!(cap_height < v_metrics.ascent);
+}
+
+#[test]
+fn underline_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let unscaled = font.v_metrics_unscaled();
+    let scale = Scale::uniform(unscaled.ascent - unscaled.descent);
+    let (position, thickness) = font.underline(scale).unwrap();
+    assert!((position + 150.0).abs() < 1e-2);
+    assert!((thickness - 100.0).abs() < 1e-2);
+
+    // Below the baseline but above the descent.
+    let scale = Scale::uniform(32.0);
+    let (position, thickness) = font.underline(scale).unwrap();
+    assert!(position < 0.0 && position > font.v_metrics(scale).descent);
+    assert!(thickness > 0.0 && thickness < 32.0 * 0.1);
+}
+
+#[test]
+fn send_sync_test() {
+    // Fails to compile if any of the types can't be shared across threads.
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Font<'static>>();
+    assert_send_sync::<FontStack<'static>>();
+    assert_send_sync::<Glyph<'static>>();
+    assert_send_sync::<GlyphOutline>();
+    assert_send_sync::<Bitmap>();
+}
+
+#[test]
+fn empty_glyph_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph(' ')
+        .build(Scale::uniform(32.0), Offset::uniform(3.0));
+    assert!(outline.shape.is_empty());
+    assert_eq!(outline.ink_bounds().width(), 0);
+
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert_eq!((sdf.width, sdf.height), (6, 6));
+    assert!(sdf.distances().iter().all(|&d| d == -2.0));
+}
+
+#[test]
+fn huge_scale_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = Offset::uniform(2.0);
+
+    // The bounds still fit but the bitmap is refused.
+    let outline = font.glyph('W').build(Scale::uniform(1e9), offset);
+    assert!(matches!(
+        outline.generate_sdf(4),
+        Err(GenError::TooLarge { .. })
+    ));
+
+    // The bounds themselves don't fit into pixel coordinates.
+    for scale in [Scale::uniform(1e12), Scale { x: -1e30, y: 1e30 }] {
+        assert!(matches!(
+            font.glyph('W').try_build(scale, offset),
+            Err(GenError::TooLarge { .. })
+        ));
+    }
+
+    let mut bbox =
+        BBox::new(Vector2::new(-2048, -2048), Vector2::new(2048, 2048));
+    bbox.scale(Scale::uniform(1e7));
+    assert_eq!(bbox.width(), i32::MAX);
+    assert_eq!(bbox.height(), i32::MAX);
+}
+
+#[test]
+fn notdef_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let notdef = font.notdef();
+    assert_eq!(notdef.id(), 0);
+
+    let outline = notdef.build(Scale::uniform(32.0), Offset::uniform(2.0));
+    assert!(!outline.shape.contours.is_empty());
+    assert!(outline.width() > 0 && outline.height() > 0);
+
+    let stack = FontStack::new(vec![font]);
+    let (glyph, index) = stack.glyph_or_notdef('\u{10FFFD}');
+    assert_eq!((glyph.id(), index), (0, 0));
+    assert_ne!(stack.glyph_or_notdef('A').0.id(), 0);
+}
+
+#[test]
+fn chars_in_range_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let basic_latin = font.chars_in_range(0x0000, 0x007F);
+    for c in ('a'..='z').chain('A'..='Z').chain('0'..='9') {
+        assert!(basic_latin.contains(&c));
+    }
+    assert!(basic_latin.iter().all(|c| c.is_ascii()));
+    assert!(basic_latin.windows(2).all(|w| w[0] < w[1]));
+
+    let greek = font.chars_in_range(0x0370, 0x03FF);
+    assert!(greek.contains(&'λ') && !greek.contains(&'a'));
+    assert!(font.chars_in_range(0x007F, 0x0000).is_empty());
+}
+
+#[test]
+fn outline_by_gid_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let (scale, offset) = (Scale::uniform(32.0), Offset::uniform(2.0));
+    let count = font.glyph_count() as u16;
+
+    let a = font.glyph('A');
+    let outline = font.outline_by_gid(a.id(), scale, offset).unwrap();
+    assert_eq!(outline.bbox(), a.build(scale, offset).bbox());
+    assert!(font.outline_by_gid(count - 1, scale, offset).is_some());
+    assert!(font.outline_by_gid(count, scale, offset).is_none());
+    assert!(font.outline_by_gid(u16::MAX, scale, offset).is_none());
+}
+
+#[test]
+fn build_with_transform_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = Offset::uniform(3.0);
+    let glyph = font.glyph('R');
+
+    let scale = Scale { x: 40.0, y: 48.0 };
+    let expected = font.glyph('R').build(scale, offset);
+    let outline =
+        glyph.build_with_transform(&Affine2::scale(40.0, 48.0), offset);
+    assert_eq!(outline.bbox(), expected.bbox());
+    assert_eq!(outline.scale(), scale);
+    assert!((outline.origin - expected.origin).magnitude() < 1e-3);
+    let diff = outline
+        .generate_sdf(6)
+        .unwrap()
+        .diff(&expected.generate_sdf(6).unwrap());
+    // Rounding differs slightly from the points rusttype outputs.
+    assert!(diff.max_abs < 0.05 && diff.mean_abs < 1e-3);
+
+    // Rotated by a quarter turn the glyph lies on its side.
+    let rotated = glyph.build_with_transform(
+        &Affine2::scale(40.0, 48.0)
+            .then(Affine2::rotation(std::f32::consts::FRAC_PI_2)),
+        offset,
+    );
+    assert!((rotated.bbox().width() - expected.bbox().height()).abs() <= 1);
+    assert!((rotated.bbox().height() - expected.bbox().width()).abs() <= 1);
+    let sdf = rotated.generate_sdf(6).unwrap();
+    assert_eq!(sdf.check_sign_consistency(), Ok(()));
+}
+
+#[test]
+fn measure_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale::uniform(32.0);
+
+    // The pair kerns negatively in the `GPOS` table.
+    let separate = font.glyph('A').advance_width(scale)
+        + font.glyph('V').advance_width(scale);
+    let measured = font.measure("AV", scale);
+    assert!(measured < separate - 0.5);
+
+    assert!(!font.has_glyph('\u{E000}'));
+    assert_eq!(font.measure("A\u{E000}V", scale), measured);
+    assert_eq!(font.measure("", scale), 0.0);
+}
+
+#[test]
+fn layout_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale::uniform(32.0);
+
+    // "f" and "i" collapse into the ligature from the `GSUB` table.
+    let placed = font.layout("fix", scale);
+    assert_eq!(placed.len(), 2);
+    let (ligature, pen) = &placed[0];
+    assert_eq!(*pen, 0.0);
+    assert_ne!(ligature.id(), font.glyph('f').id());
+    assert_ne!(ligature.id(), font.glyph('i').id());
+    assert_eq!(placed[1].0.id(), font.glyph('x').id());
+    assert!(placed[1].1 > 0.0);
+
+    // Without a ligature every character gets its own glyph.
+    let placed = font.layout("AV", scale);
+    assert_eq!(placed.len(), 2);
+    assert_eq!(placed[0].0.id(), font.glyph('A').id());
+    assert!(placed[1].1 < font.glyph('A').advance_width(scale));
+}
+
+#[test]
+fn try_generate_sdf_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale::uniform(24.0);
+
+    let sdf = font.try_generate_sdf('A', scale, 4).unwrap();
+    let outline = font.glyph('A').build(scale, Offset::uniform(2.0));
+    assert_eq!(
+        sdf.distances(),
+        outline.generate_sdf(4).unwrap().distances()
+    );
+
+    assert_eq!(
+        font.try_generate_sdf('\u{E000}', scale, 4).unwrap_err(),
+        GenError::MissingGlyph('\u{E000}')
+    );
+    for scale in [0.0, f32::NAN, f32::INFINITY] {
+        assert_eq!(
+            font.try_generate_sdf('A', Scale::uniform(scale), 4)
+                .unwrap_err(),
+            GenError::InvalidScale
+        );
+    }
+    assert!(matches!(
+        font.try_generate_sdf('A', Scale::uniform(1e9), 4),
+        Err(GenError::TooLarge { .. })
+    ));
+
+    let cubic =
+        Font::from_slice(include_bytes!("../examples/fonts/Cantarell-VF.otf"));
+    assert!(cubic.try_generate_sdf('o', scale, 4).is_ok());
+
+    // Degenerate input gives an error or an empty field. At a tiny scale
+    // every point of the outline collapses into one.
+    assert_eq!(
+        font.try_generate_sdf('A', scale, 0).unwrap_err(),
+        GenError::InvalidRange
+    );
+    let collapsed = font.try_generate_sdf('A', Scale::uniform(1e-9), 4);
+    assert!(collapsed.unwrap().distances().iter().all(|&d| d == -2.0));
+}
+
+#[test]
+fn outline_with_test() {
+    #[derive(Default)]
+    struct Counter {
+        moves: usize,
+        lines: usize,
+        quads: usize,
+        curves: usize,
+        closes: usize,
+    }
+
+    impl OutlineBuilder for Counter {
+        fn move_to(&mut self, _: f32, _: f32) {
+            self.moves += 1;
+        }
+        fn line_to(&mut self, _: f32, _: f32) {
+            self.lines += 1;
+        }
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {
+            self.quads += 1;
+        }
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {
+            self.curves += 1;
+        }
+        fn close(&mut self) {
+            self.closes += 1;
+        }
+    }
+
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    // The stem of `I` is a single rectangle.
+    let mut counter = Counter::default();
+    let rect = font.glyph('I').outline_with(&mut counter).unwrap();
+    assert_eq!((counter.moves, counter.closes), (1, 1));
+    assert_eq!((counter.lines, counter.quads, counter.curves), (4, 0, 0));
+    assert_eq!(
+        (rect.x_min, rect.y_min, rect.x_max, rect.y_max),
+        (183, 0, 375, 1456)
+    );
+
+    let mut counter = Counter::default();
+    font.glyph('o').outline_with(&mut counter).unwrap();
+    assert_eq!((counter.moves, counter.closes), (2, 2));
+    assert_eq!((counter.lines, counter.quads, counter.curves), (2, 18, 0));
+
+    let mut counter = Counter::default();
+    assert!(font.glyph(' ').outline_with(&mut counter).is_none());
+    assert_eq!(counter.moves, 0);
+}
+
+#[test]
+fn outline_format_test() {
+    let roboto = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    assert_eq!(roboto.outline_format(), OutlineFormat::TrueType);
+
+    let cantarell =
+        Font::from_slice(include_bytes!("../examples/fonts/Cantarell-VF.otf"));
+    assert_eq!(cantarell.outline_format(), OutlineFormat::PostScript);
+}
+
+#[test]
+fn post_script_fill_test() {
+    let font =
+        Font::from_slice(include_bytes!("../examples/fonts/Cantarell-VF.otf"));
+    let outline = font
+        .glyph('I')
+        .build(Scale::uniform(32.0), Offset::uniform(2.0));
+
+    // The last contour of `CFF2` glyphs isn't closed by an instruction.
+    let shape = &outline.shape;
+    assert!(shape.validate().is_empty());
+    assert!(shape.contours_with_role().all(|(_, fill)| fill));
+
+    let sdf = outline.generate_sdf(4).unwrap();
+    let center = (sdf.width / 2, sdf.height / 2);
+    assert!(sdf.distance(center.0, center.1) > 0.0);
+    assert!(sdf.distance(0, 0) < 0.0);
+}
+
+#[test]
+fn compose_test() {
+    let font =
+        Font::from_slice(include_bytes!("../examples/fonts/monserat.ttf"));
+    let scale = Scale::uniform(48.0);
+    let base = font.glyph('a').build(scale, Offset::uniform(0.0));
+    let composed = font.compose('a', '\u{301}', scale).unwrap();
+
+    assert_eq!(composed.contours.len(), base.shape.contours.len() + 1);
+    let bbox = composed.bbox();
+    assert_eq!(bbox.tl, Vector2::ZERO_I32);
+    // The accent sits above the base, making the shape taller.
+    assert!(bbox.height() > base.height() + 5);
+    assert!(bbox.width() <= base.width() + 2);
+
+    // Shapes are ready for generation.
+    let outline =
+        GlyphOutline::from_shape(composed, bbox, scale, Offset::uniform(0.0));
+    assert!(outline.generate_sdf(4).is_ok());
+    // Letters don't have anchors for other letters.
+    assert!(font.compose('a', 'b', scale).is_none());
+}
+
+#[test]
+fn render_string_test() {
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let scale = Scale::uniform(32.0);
+    let sdf = font.render_string_sdf("Hi", scale, 4).unwrap();
+
+    let h = font.glyph('H');
+    let advance = h.advance_width(scale) + font.glyph('i').advance_width(scale);
+    assert!(sdf.width as f32 >= advance);
+    assert!(sdf.width as f32 <= advance + 8.0);
+
+    // Both glyphs are inside of the field.
+    let inside_columns = (0..sdf.width)
+        .filter(|&x| (0..sdf.height).any(|y| sdf.distance(x, y) > 0.0))
+        .collect::<Vec<_>>();
+    assert!(inside_columns[0] < sdf.width / 4);
+    assert!(*inside_columns.last().unwrap() > sdf.width * 3 / 4);
+    // The gap between the letters stays outside.
+    let h_width = h.build(scale, Offset::uniform(0.0)).width() as u32;
+    assert!(inside_columns.windows(2).any(|w| w[1] - w[0] > 1));
+    assert!(inside_columns.len() as u32 > h_width);
+
+    let blank = font.render_string_sdf("  ", scale, 4).unwrap();
+    assert!(blank.width > 0 && blank.distances().iter().all(|&d| d == -2.0));
+
+    for text in ["Hi", "  "] {
+        let result = font.render_string_sdf(text, scale, 0);
+        assert!(matches!(result, Err(GenError::InvalidRange)));
+        // Too large fields fail before anything is allocated.
+        let huge = Scale::uniform(200000.0);
+        let result = font.render_string_sdf(text, huge, 4);
+        assert!(matches!(result, Err(GenError::TooLarge { .. })));
+    }
 }
 
 // TODO is needed?? /// Used for scaling glyphs and getting the desired output dimensions.
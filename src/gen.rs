@@ -1,83 +1,2347 @@
+use rusttype::Scale;
+
 use crate::{
-    font::GlyphOutline, math::Distance, shape::Shape, vector::Vector2,
+    coloring::{color_shape, EdgeColor, DEFAULT_ANGLE_THRESHOLD},
+    font::{BBox, GlyphOutline, Offset},
+    math::Distance,
+    path::PathBuilder,
+    shape::{Shape, Winding},
+    vector::{Affine2, Vector2},
 };
 
+/// Largest width or height of a generated bitmap used when no other limit
+/// is set on the [`GlyphOutline`].
+pub const DEFAULT_MAX_DIMENSION: u32 = 16384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenError {
+    /// The bitmap would exceed the maximum dimension of the outline.
+    TooLarge { width: u64, height: u64 },
+    /// The font has no glyph for the character.
+    MissingGlyph(char),
+    /// The scale is zero, infinite or `NaN` on one of the axes.
+    InvalidScale,
+    /// The range is zero, which leaves no room for distances and can't be
+    /// normalized.
+    InvalidRange,
+    /// The shape can't be built from the input, like a polygon with fewer
+    /// than three points or a malformed glyph outline.
+    InvalidShape,
+    /// The number of raw distances doesn't match the dimensions of the
+    /// bitmap.
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for GenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::TooLarge { width, height } => write!(
+                f,
+                "Generation Error: The bitmap of {}x{} pixels is too large!",
+                width, height
+            ),
+            GenError::MissingGlyph(c) => write!(
+                f,
+                "Generation Error: The font has no glyph for {:?}!",
+                c
+            ),
+            GenError::InvalidScale => write!(
+                f,
+                "Generation Error: The scale has to be finite and non-zero!"
+            ),
+            GenError::InvalidRange => write!(
+                f,
+                "Generation Error: The range has to be at least one pixel!"
+            ),
+            GenError::InvalidShape => write!(
+                f,
+                "Generation Error: The input doesn't form a valid shape!"
+            ),
+            GenError::SizeMismatch { expected, actual } => write!(
+                f,
+                "Generation Error: Expected {} distances but got {}!",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenError {}
+
+/// Which side of the edge gets the values above `0.5` when the distances
+/// are normalized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    /// The inside of the shape is above `0.5`. This is the default and
+    /// matches the sign of the distances.
+    #[default]
+    InsidePositive,
+    /// The inside of the shape is below `0.5`.
+    InsideNegative,
+}
+
+/// Bitmap holding a signed distance for every pixel channel.
+///
+/// Distances are measured in pixels (texels of the output), so a distance of
+/// `1.0` is the size of one output pixel, no matter the scale the shape was
+/// built with. They are positive inside of the shape and are clamped to the
+/// interval `[-range / 2, range / 2]`. Channels of a pixel are stored next
+/// to each other.
+#[derive(Debug, Clone)]
 pub struct Bitmap {
-    pub data: Vec<u8>,
+    pub(crate) distances: Vec<f32>,
     pub width: u32,
     pub height: u32,
+    pub channels: u32,
+    pub(crate) range: f32,
+    pub(crate) debug: Option<GenDebug>,
+    /// Maps positions in the bitmap to the coordinates of the source.
+    pub(crate) to_shape: Affine2,
+}
+
+/// Information about the generation attached to a [`Bitmap`] by
+/// [`gen_sdf_debug`], meant for tracking down wrong signs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenDebug {
+    /// Winding of every contour of the shape, in the same order as the
+    /// contours, after [`Shape::normalize_windings`].
+    pub windings: Vec<Winding>,
 }
 
 impl Bitmap {
-    pub fn data(self) -> Vec<u8> {
-        self.data
+    /// Creates a bitmap where every pixel is as far outside as the
+    /// `range` allows.
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        channels: u32,
+        range: f32,
+    ) -> Self {
+        Self {
+            distances: vec![
+                -0.5 * range;
+                width as usize * height as usize * channels as usize
+            ],
+            width,
+            height,
+            channels,
+            range,
+            debug: None,
+            to_shape: Affine2::IDENTITY,
+        }
+    }
+
+    /// Creates a bitmap from raw signed distances in pixels, positive
+    /// inside, with the channels of a pixel next to each other.
+    ///
+    /// Useful for loading precomputed fields or for testing the
+    /// post-processing like [`Bitmap::stroke`] without a font. The range is
+    /// twice the largest finite distance, so no distance is out of it, or
+    /// `1.0` if all distances are zero.
+    ///
+    /// Fails with [`GenError::TooLarge`] if a dimension doesn't fit into
+    /// `u32` and with [`GenError::SizeMismatch`] if the length of the
+    /// `data` doesn't match the dimensions.
+    pub fn from_raw(
+        width: usize,
+        height: usize,
+        channels: usize,
+        data: Vec<f32>,
+    ) -> Result<Bitmap, GenError> {
+        let too_large = GenError::TooLarge {
+            width: width as u64,
+            height: height as u64,
+        };
+        let len = match width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+        {
+            Some(len) => len,
+            None => return Err(too_large),
+        };
+        let (width, height, channels) = match (
+            u32::try_from(width),
+            u32::try_from(height),
+            u32::try_from(channels),
+        ) {
+            (Ok(width), Ok(height), Ok(channels)) => (width, height, channels),
+            _ => return Err(too_large),
+        };
+        if len != data.len() {
+            return Err(GenError::SizeMismatch {
+                expected: len,
+                actual: data.len(),
+            });
+        }
+        let largest = data
+            .iter()
+            .filter(|d| d.is_finite())
+            .fold(0.0f32, |max, d| max.max(d.abs()));
+        let range = if largest > 0.0 { 2.0 * largest } else { 1.0 };
+
+        Ok(Bitmap {
+            distances: data,
+            width,
+            height,
+            channels,
+            range,
+            debug: None,
+            to_shape: Affine2::IDENTITY,
+        })
+    }
+
+    /// Returns the distances normalized to bytes, where `127` represents
+    /// the edge of the shape.
+    pub fn data(&self) -> Vec<u8> {
+        self.data_with(Polarity::default())
+    }
+
+    /// Same as [`Bitmap::data`] but with the provided [`Polarity`].
+    pub fn data_with(&self, polarity: Polarity) -> Vec<u8> {
+        self.normalized_distances_with(polarity)
+            .into_iter()
+            // When f32 is being converted to u8 it is automatically
+            // clamped in range [0, 255].
+            .map(|normalized| (normalized * 255.0) as u8)
+            .collect()
+    }
+
+    /// Returns the distances quantized to integers of `bits` bits for
+    /// textures with more precision than [`Bitmap::data`], like 16-bit
+    /// channels.
+    ///
+    /// Distances are normalized to the provided `range` the same way as
+    /// by [`Bitmap::normalized_distances`] and mapped to `[0, 2^bits - 1]`,
+    /// so 8 bits with the range of the bitmap give the same values as
+    /// [`Bitmap::data`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` isn't between `1` and `16` or if the `range` isn't
+    /// positive.
+    pub fn quantize(&self, bits: u8, range: f32) -> Vec<u16> {
+        assert!(
+            (1..=16).contains(&bits),
+            "Bitmap Error: Only 1 to 16 bits are supported."
+        );
+        assert!(range > 0.0, "Bitmap Error: The range must be positive.");
+        let max = ((1u32 << bits) - 1) as f32;
+        self.distances
+            .iter()
+            .map(|distance| {
+                let normalized = (distance / range + 0.5).clamp(0.0, 1.0);
+                (normalized * max) as u16
+            })
+            .collect()
+    }
+
+    /// Returns the distances mapped to the interval `[0, 1]` the same way
+    /// a shader reads them from a texture, where `0.5` is the edge of the
+    /// shape.
+    ///
+    /// A texel distance is recovered with `(value - 0.5) * range`.
+    pub fn normalized_distances(&self) -> Vec<f32> {
+        self.normalized_distances_with(Polarity::default())
+    }
+
+    /// Same as [`Bitmap::normalized_distances`] but with the provided
+    /// [`Polarity`]. With [`Polarity::InsideNegative`] a texel distance is
+    /// recovered with `(0.5 - value) * range`.
+    pub fn normalized_distances_with(&self, polarity: Polarity) -> Vec<f32> {
+        let factor = match polarity {
+            Polarity::InsidePositive => 1.0 / self.range,
+            Polarity::InsideNegative => -1.0 / self.range,
+        };
+        self.distances
+            .iter()
+            .map(|distance| distance * factor + 0.5)
+            .collect()
+    }
+
+    /// Returns the signed distances in pixels.
+    #[inline]
+    pub fn distances(&self) -> &[f32] {
+        &self.distances
+    }
+
+    /// Returns the signed distance in the first channel of the pixel at
+    /// the provided position.
+    #[inline]
+    pub fn distance(&self, x: u32, y: u32) -> f32 {
+        self.pixel(x, y)[0]
+    }
+
+    /// Returns the signed distances of all channels of the pixel at
+    /// the provided position.
+    #[inline]
+    pub fn pixel(&self, x: u32, y: u32) -> &[f32] {
+        let start = (y as usize * self.width as usize + x as usize)
+            * self.channels as usize;
+        &self.distances[start..start + self.channels as usize]
+    }
+
+    /// Returns a single channel bitmap with a copy of the channel at
+    /// `index`.
+    ///
+    /// Panics if the bitmap doesn't have the channel.
+    pub fn channel(&self, index: usize) -> Bitmap {
+        let channels = self.channels as usize;
+        assert!(
+            index < channels,
+            "Bitmap Error: Channel {} is out of {} channels.",
+            index,
+            channels
+        );
+        let distances = self
+            .distances
+            .iter()
+            .skip(index)
+            .step_by(channels)
+            .copied()
+            .collect();
+
+        Bitmap {
+            distances,
+            channels: 1,
+            debug: self.debug.clone(),
+            ..*self
+        }
+    }
+
+    /// Combines the channels of all provided bitmaps, in the provided
+    /// order, into a single bitmap.
+    ///
+    /// Panics if there are no bitmaps or if their dimensions or ranges
+    /// differ.
+    pub fn merge_channels(bitmaps: &[Bitmap]) -> Bitmap {
+        let first = bitmaps
+            .first()
+            .expect("Bitmap Error: There are no bitmaps to merge.");
+        assert!(
+            bitmaps.iter().all(|b| b.width == first.width
+                && b.height == first.height
+                && b.range == first.range),
+            "Bitmap Error: Merged bitmaps must have the same dimensions \
+            and range."
+        );
+
+        let channels: u32 = bitmaps.iter().map(|b| b.channels).sum();
+        let pixels = first.width as usize * first.height as usize;
+        let mut distances = Vec::with_capacity(pixels * channels as usize);
+        for pixel in 0..pixels {
+            for bitmap in bitmaps {
+                let count = bitmap.channels as usize;
+                distances.extend_from_slice(
+                    &bitmap.distances[pixel * count..(pixel + 1) * count],
+                );
+            }
+        }
+
+        Bitmap {
+            distances,
+            channels,
+            debug: first.debug.clone(),
+            ..*first
+        }
+    }
+
+    /// Returns the information attached by [`gen_sdf_debug`], if the
+    /// bitmap was generated with it.
+    #[inline]
+    pub fn debug(&self) -> Option<&GenDebug> {
+        self.debug.as_ref()
     }
+
+    /// Converts a position in the bitmap, in pixels with `(0.5, 0.5)` at the
+    /// center of the top left pixel, to the coordinates of the shape the
+    /// bitmap was generated from.
+    ///
+    /// Accounts for the shift of [`gen_sdf_shifted`] and for the scale of
+    /// [`sdf_from_polygon`], which maps to the polygon coordinates.
+    #[inline]
+    pub fn pixel_to_shape(&self, px: f32, py: f32) -> Vector2<f32> {
+        self.to_shape.transform(Vector2::new(px, py))
+    }
+
+    /// Converts shape coordinates to a position in the bitmap, the inverse
+    /// of [`Bitmap::pixel_to_shape`].
+    pub fn shape_to_pixel(&self, x: f32, y: f32) -> Vector2<f32> {
+        self.to_shape
+            .inverse()
+            .expect("Bitmap Error: The shape transform can't be inverted!")
+            .transform(Vector2::new(x, y))
+    }
+
+    /// Generates the signed distance field of the `outline` into this
+    /// bitmap, the same as [`gen_sdf`] would.
+    ///
+    /// The buffer is overwritten in place and only reallocated when the new
+    /// field needs more space, so a cached tile can be updated without an
+    /// allocation, e.g. when an axis of a variable font changes. The bitmap
+    /// is left untouched if the generation fails.
+    pub fn regenerate_from(
+        &mut self,
+        outline: &GlyphOutline,
+        range: usize,
+    ) -> Result<(), GenError> {
+        let shift = Vector2::new(0.0, 0.0);
+        gen_sdf_into(self, outline, range, shift, None, true, true)
+    }
+
+    /// Returns the range of distances the bitmap was generated with.
+    #[inline]
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    /// Returns a field where only the band of `half_width` pixels around
+    /// the edge of the shape is inside, turning the shape into its
+    /// outline.
+    pub fn stroke(&self, half_width: f32) -> Bitmap {
+        let half_range = 0.5 * self.range;
+        let distances = self
+            .distances
+            .iter()
+            .map(|d| (half_width - d.abs()).clamp(-half_range, half_range))
+            .collect();
+
+        Bitmap {
+            distances,
+            debug: self.debug.clone(),
+            ..*self
+        }
+    }
+
+    /// Returns the field downscaled by `factor` in both dimensions, like a
+    /// mip level of an atlas.
+    ///
+    /// Instead of averaging the distances, which drifts near corners, the
+    /// distances are recomputed from the zero crossings of the original
+    /// field, found between neighbouring pixels of opposite signs. The sign
+    /// comes from the original field interpolated at the center of the
+    /// downscaled pixel. Distances stay in the downscaled pixels, so the
+    /// range shrinks by the `factor` too and the normalized field keeps
+    /// the same values.
+    ///
+    /// Only edge points within half of the range can give a distance which
+    /// isn't clamped, so the points are sorted into cells of that size and
+    /// each pixel searches just the cells around it.
+    ///
+    /// Panics if `factor` is `0`.
+    pub fn downscale_sdf(&self, factor: usize) -> Bitmap {
+        assert!(factor > 0, "Bitmap Error: The factor must be positive!");
+        let scale = factor as f32;
+        let width = self.width.div_ceil(factor as u32);
+        let height = self.height.div_ceil(factor as u32);
+        let channels = self.channels as usize;
+        let range = self.range / scale;
+        let half_range = 0.5 * range;
+        let distance = |x: u32, y: u32, ch: usize| {
+            let x = x.min(self.width - 1);
+            let y = y.min(self.height - 1);
+            self.distances[(y * self.width + x) as usize * channels + ch]
+        };
+
+        // Distances farther than this in the original pixels are clamped.
+        let reach = 0.5 * self.range;
+        let cell = reach.max(1.0);
+        let columns = (self.width as f32 / cell).ceil() as usize;
+        let rows = (self.height as f32 / cell).ceil() as usize;
+        let cell_of =
+            |coord: f32, count: usize| ((coord / cell) as usize).min(count - 1);
+
+        // Points of the edge in the original pixels, where the distance
+        // changes its sign between two neighbouring pixel centers, sorted
+        // into the cells of a grid for every channel.
+        let mut edges = vec![vec![Vec::new(); columns * rows]; channels];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for (ch, edges) in edges.iter_mut().enumerate() {
+                    let d = distance(x, y, ch);
+                    let center = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let neighbours = [
+                        (x + 1 < self.width, distance(x + 1, y, ch), 1.0, 0.0),
+                        (y + 1 < self.height, distance(x, y + 1, ch), 0.0, 1.0),
+                    ];
+                    for (exists, n, dx, dy) in neighbours {
+                        if exists && (d > 0.0) != (n > 0.0) {
+                            let t = d / (d - n);
+                            let edge = center + t * Vector2::new(dx, dy);
+                            let column = cell_of(edge.x, columns);
+                            let row = cell_of(edge.y, rows);
+                            edges[row * columns + column].push(edge);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut distances =
+            Vec::with_capacity(width as usize * height as usize * channels);
+        for y in 0..height {
+            for x in 0..width {
+                let center = Vector2::new(
+                    (x as f32 + 0.5) * scale,
+                    (y as f32 + 0.5) * scale,
+                );
+                // Bilinear interpolation between the surrounding centers.
+                let sx = (center.x - 0.5).max(0.0);
+                let sy = (center.y - 0.5).max(0.0);
+                let (x0, y0) = (sx as u32, sy as u32);
+                let (tx, ty) = (sx.fract(), sy.fract());
+                for (ch, edges) in edges.iter().enumerate() {
+                    let top = distance(x0, y0, ch) * (1.0 - tx)
+                        + distance(x0 + 1, y0, ch) * tx;
+                    let bottom = distance(x0, y0 + 1, ch) * (1.0 - tx)
+                        + distance(x0 + 1, y0 + 1, ch) * tx;
+                    let sign = if top * (1.0 - ty) + bottom * ty > 0.0 {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    let first_column =
+                        cell_of((center.x - reach).max(0.0), columns);
+                    let last_column = cell_of(center.x + reach, columns);
+                    let first_row = cell_of((center.y - reach).max(0.0), rows);
+                    let last_row = cell_of(center.y + reach, rows);
+                    let nearest = (first_row..=last_row)
+                        .flat_map(|row| {
+                            let start = row * columns;
+                            edges[start + first_column..=start + last_column]
+                                .iter()
+                                .flatten()
+                        })
+                        .map(|&edge| (edge - center).magnitude())
+                        .fold(f32::INFINITY, f32::min);
+                    distances.push(
+                        (sign * nearest / scale).clamp(-half_range, half_range),
+                    );
+                }
+            }
+        }
+
+        Bitmap {
+            distances,
+            width,
+            height,
+            channels: self.channels,
+            range,
+            debug: self.debug.clone(),
+            to_shape: Affine2::scale(scale, scale).then(self.to_shape),
+        }
+    }
+
+    /// Copies the `src` bitmap into this one with its top left pixel at
+    /// `x` and `y`, overwriting the distances of the covered pixels.
+    ///
+    /// Useful for assembling an atlas manually, this is what
+    /// [`AtlasBuilder`](crate::AtlasBuilder) places its tiles with.
+    ///
+    /// Panics if the bitmaps have a different number of channels or if the
+    /// `src` doesn't fit at the position.
+    pub fn blit(&mut self, src: &Bitmap, x: usize, y: usize) {
+        assert_eq!(
+            self.channels, src.channels,
+            "Bitmap Error: The bitmaps have different numbers of channels!"
+        );
+        assert!(
+            x + src.width as usize <= self.width as usize
+                && y + src.height as usize <= self.height as usize,
+            "Bitmap Error: The source bitmap doesn't fit at ({}, {})!",
+            x,
+            y
+        );
+        let channels = self.channels as usize;
+        let row_len = src.width as usize * channels;
+        for row in 0..src.height as usize {
+            let from = row * row_len;
+            let to = ((y + row) * self.width as usize + x) * channels;
+            self.distances[to..to + row_len]
+                .copy_from_slice(&src.distances[from..from + row_len]);
+        }
+    }
+
+    /// Grows the shape by `amount` pixels in every direction.
+    ///
+    /// Distances which end up out of the range are clamped, so the
+    /// operation can only be undone near the edge of the shape.
+    pub fn dilate(&mut self, amount: f32) {
+        self.shift(amount);
+    }
+
+    /// Shrinks the shape by `amount` pixels in every direction.
+    ///
+    /// Distances which end up out of the range are clamped, so the
+    /// operation can only be undone near the edge of the shape.
+    pub fn erode(&mut self, amount: f32) {
+        self.shift(-amount);
+    }
+
+    /// Replaces every `NaN` or infinite distance with the average of its
+    /// finite neighbours and returns the number of replaced pixels.
+    ///
+    /// Neighbours are read before anything is replaced, so the result
+    /// doesn't depend on the order of the pixels. If a pixel has no finite
+    /// neighbours, infinities are clamped to the range and `NaN`s are
+    /// treated as far outside.
+    pub fn sanitize(&mut self) -> usize {
+        let half_range = 0.5 * self.range;
+        let width = self.width as i64;
+        let height = self.height as i64;
+        let channels = self.channels as i64;
+        let index = |x: i64, y: i64, ch: i64| {
+            ((y * width + x) * channels + ch) as usize
+        };
+        let original = self.distances.clone();
+        let mut scrubbed = 0;
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut replaced = false;
+                for ch in 0..channels {
+                    let distance = original[index(x, y, ch)];
+                    if distance.is_finite() {
+                        continue;
+                    }
+
+                    let mut sum = 0.0;
+                    let mut count = 0;
+                    for ny in (y - 1).max(0)..=(y + 1).min(height - 1) {
+                        for nx in (x - 1).max(0)..=(x + 1).min(width - 1) {
+                            let neighbour = original[index(nx, ny, ch)];
+                            if neighbour.is_finite() {
+                                sum += neighbour;
+                                count += 1;
+                            }
+                        }
+                    }
+
+                    self.distances[index(x, y, ch)] = if count > 0 {
+                        sum / count as f32
+                    } else if distance.is_nan() {
+                        -half_range
+                    } else {
+                        distance.clamp(-half_range, half_range)
+                    };
+                    replaced = true;
+                }
+                if replaced {
+                    scrubbed += 1;
+                }
+            }
+        }
+
+        scrubbed
+    }
+
+    /// Returns the fraction of pixels whose distance in the first channel
+    /// is above `threshold`, which is `0.0` for the edge of the shape.
+    ///
+    /// Useful for finding how much of a tile is covered by ink, like when
+    /// choosing tile sizes or looking for nearly empty glyphs. Returns
+    /// `0.0` for a bitmap without pixels.
+    pub fn ink_ratio(&self, threshold: f32) -> f32 {
+        let pixels = self.width as usize * self.height as usize;
+        if pixels == 0 {
+            return 0.0;
+        }
+        let inked = self
+            .distances
+            .iter()
+            .step_by(self.channels as usize)
+            .filter(|&&distance| distance > threshold)
+            .count();
+        inked as f32 / pixels as f32
+    }
+
+    /// Checks that the signs of the first channel agree with each other by
+    /// flood filling the field from the top left pixel, which is expected
+    /// to be outside.
+    ///
+    /// The fill spreads to a neighbour unless the two pixels are separated
+    /// by the edge of the shape, which is only the case when their signs
+    /// differ and the distances change by at most a pixel between them.
+    /// Returns the positions of all reached pixels with a positive distance
+    /// since they are inside without the shape being crossed, which points
+    /// to a wrong sign.
+    pub fn check_sign_consistency(&self) -> Result<(), Vec<(usize, usize)>> {
+        // Distances of neighbouring pixels can't differ by more than a
+        // pixel, the rest is a tolerance for the precision of the distances.
+        const MAX_STEP: f32 = 1.5;
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let distance = |x: usize, y: usize| self.distance(x as u32, y as u32);
+
+        let mut reached = vec![false; width * height];
+        let mut stack = vec![(0, 0)];
+        reached[0] = true;
+        while let Some((x, y)) = stack.pop() {
+            let current = distance(x, y);
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbours {
+                if nx >= width || ny >= height || reached[ny * width + nx] {
+                    continue;
+                }
+                let next = distance(nx, ny);
+                let crosses_edge = (current > 0.0) != (next > 0.0)
+                    && (current - next).abs() <= MAX_STEP;
+                if !crosses_edge {
+                    reached[ny * width + nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+
+        let flipped = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| reached[y * width + x] && distance(x, y) > 0.0)
+            .collect::<Vec<_>>();
+        if flipped.is_empty() {
+            Ok(())
+        } else {
+            Err(flipped)
+        }
+    }
+
+    /// Compares the distances with another bitmap of the same dimensions
+    /// and number of channels.
+    ///
+    /// Useful for checking that a change to the generation doesn't alter
+    /// the output.
+    pub fn diff(&self, other: &Bitmap) -> BitmapDiff {
+        assert!(
+            self.width == other.width
+                && self.height == other.height
+                && self.channels == other.channels,
+            "Bitmap Error: Compared bitmaps have different dimensions!"
+        );
+
+        let mut diff = BitmapDiff::default();
+        let mut sum = 0.0;
+        let channels = self.channels as usize;
+        for (a, b) in self
+            .distances
+            .chunks(channels)
+            .zip(other.distances.chunks(channels))
+        {
+            let mut mismatched = false;
+            for (a, b) in a.iter().zip(b) {
+                let abs = (a - b).abs();
+                diff.max_abs = diff.max_abs.max(abs);
+                sum += abs;
+                mismatched |= abs > 0.0;
+            }
+            if mismatched {
+                diff.mismatched_pixels += 1;
+            }
+        }
+        if !self.distances.is_empty() {
+            diff.mean_abs = sum / self.distances.len() as f32;
+        }
+
+        diff
+    }
+
+    fn shift(&mut self, amount: f32) {
+        let half_range = 0.5 * self.range;
+        for distance in self.distances.iter_mut() {
+            *distance = (*distance + amount).clamp(-half_range, half_range);
+        }
+    }
+}
+
+/// Difference between two bitmaps returned by [`Bitmap::diff`].
+///
+/// Differences are measured in pixels over every channel.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BitmapDiff {
+    /// Largest absolute difference of two distances.
+    pub max_abs: f32,
+    /// Average absolute difference of the distances.
+    pub mean_abs: f32,
+    /// Number of pixels which differ in at least one channel.
+    pub mismatched_pixels: usize,
+}
+
+#[cfg(feature = "image")]
+impl Bitmap {
+    /// Converts a single channel bitmap into a grayscale image with the
+    /// distances normalized the same way as in [`Bitmap::data`].
+    pub fn to_gray_image(&self) -> image::GrayImage {
+        assert_eq!(
+            self.channels, 1,
+            "Bitmap Error: Only single channel bitmaps are grayscale."
+        );
+        image::GrayImage::from_raw(self.width, self.height, self.data())
+            .expect("Bitmap Error: Data doesn't match the dimensions.")
+    }
+
+    /// Converts the bitmap into an RGB image.
+    ///
+    /// Single channel bitmaps get the same value in every image channel.
+    pub fn to_rgb_image(&self) -> image::RgbImage {
+        let data = match self.channels {
+            1 => self.data().into_iter().flat_map(|v| [v, v, v]).collect(),
+            3 => self.data(),
+            _ => panic!("Bitmap Error: Only 1 or 3 channels can be RGB."),
+        };
+        image::RgbImage::from_raw(self.width, self.height, data)
+            .expect("Bitmap Error: Data doesn't match the dimensions.")
+    }
+}
+
+pub fn gen_sdf(
+    outline: &GlyphOutline,
+    range: usize,
+) -> Result<Bitmap, GenError> {
+    gen_sdf_with(outline, range, Vector2::new(0.0, 0.0), true)
+}
+
+/// Same as [`gen_sdf`] but attaches the [`GenDebug`] information to the
+/// returned bitmap.
+pub fn gen_sdf_debug(
+    outline: &GlyphOutline,
+    range: usize,
+) -> Result<Bitmap, GenError> {
+    let mut bitmap = gen_sdf(outline, range)?;
+    bitmap.debug = Some(GenDebug {
+        windings: outline.shape.normalized_windings(),
+    });
+    Ok(bitmap)
+}
+
+/// Same as [`gen_sdf`] but the distances are sampled at pixel centers moved
+/// by the fractional `shift` in pixels.
+pub fn gen_sdf_shifted(
+    outline: &GlyphOutline,
+    range: usize,
+    shift: Vector2<f32>,
+) -> Result<Bitmap, GenError> {
+    gen_sdf_with(outline, range, shift, true)
+}
+
+/// Same as [`gen_sdf`] but also returns a hard coverage mask of the same
+/// size, for renderers mixing the field with exact coverage.
+///
+/// The mask is derived from the signs of the generated distances, so the
+/// shape is only measured once. It has the range `1.0` with the distance
+/// `0.5` inside and `-0.5` outside, so its normalized values are `1.0`
+/// and `0.0`.
+pub fn gen_sdf_with_mask(
+    outline: &GlyphOutline,
+    range: usize,
+) -> Result<(Bitmap, Bitmap), GenError> {
+    let sdf = gen_sdf(outline, range)?;
+    let distances = sdf
+        .distances
+        .iter()
+        .map(|&d| if d > 0.0 { 0.5 } else { -0.5 })
+        .collect();
+    let mask = Bitmap {
+        distances,
+        range: 1.0,
+        debug: None,
+        ..sdf
+    };
+
+    Ok((sdf, mask))
 }
 
-pub fn gen_sdf(outline: &GlyphOutline, range: usize) -> Bitmap {
+/// Same as [`gen_sdf`] but the distances aren't clamped to the range, so
+/// every pixel stores the true distance to the edge, which is useful for
+/// geometric analysis of the field rather than for textures.
+///
+/// The `range` is still stored for normalizing the distances. The field
+/// of an empty shape stays at the far outside of the range since there is
+/// no edge to measure the distance to.
+pub fn gen_sdf_unclamped(
+    outline: &GlyphOutline,
+    range: usize,
+) -> Result<Bitmap, GenError> {
+    let mut bitmap = Bitmap::new(0, 0, 1, range as f32);
+    let shift = Vector2::new(0.0, 0.0);
+    gen_sdf_into(&mut bitmap, outline, range, shift, None, false, false)?;
+    Ok(bitmap)
+}
+
+/// Same as [`gen_sdf`] but every pixel is sampled at a point randomly
+/// moved by up to `amount` pixels on both axes from its center.
+///
+/// The movement is deterministic for the `seed`, so generations with the
+/// same seed are identical. Meant for testing the numerical stability of
+/// the field, which should only change by the distance the samples moved.
+pub fn gen_sdf_jittered(
+    outline: &GlyphOutline,
+    range: usize,
+    seed: u64,
+    amount: f32,
+) -> Result<Bitmap, GenError> {
+    let mut bitmap = Bitmap::new(0, 0, 1, range as f32);
+    let shift = Vector2::new(0.0, 0.0);
+    let jittered = Some((seed, amount));
+    gen_sdf_into(&mut bitmap, outline, range, shift, jittered, true, true)?;
+    Ok(bitmap)
+}
+
+/// Returns the largest distance from the edge to a point inside of the
+/// outline, half of the thickness of its thickest part.
+///
+/// The distance is sampled at the pixel centers inside of the shape, so it
+/// is at most half of a pixel diagonal below the exact value. An empty
+/// shape has no inside and returns `0.0`.
+pub fn max_interior_distance(outline: &GlyphOutline) -> f32 {
+    let shape = &outline.shape;
+    if shape.is_empty() {
+        return 0.0;
+    }
+    let shape_distance = ShapeDistance::new(shape);
+    let (min, max) = shape.bounds();
+
+    let mut deepest = 0.0f32;
+    for y in min.y.floor() as i32..max.y.ceil() as i32 {
+        for x in min.x.floor() as i32..max.x.ceil() as i32 {
+            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            deepest = deepest.max(shape_distance.signed_distance(pixel));
+        }
+    }
+
+    deepest
+}
+
+/// Returns a pseudo-random offset with both coordinates in `[-1, 1]`
+/// for the pixel at `index`, always the same for the same `seed`.
+fn jitter(seed: u64, index: u64) -> Vector2<f32> {
+    // SplitMix64 of the seed combined with the index.
+    let mut z = seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    let unit = |bits: u64| (bits & 0xFFFF_FFFF) as f32 / u32::MAX as f32;
+
+    Vector2::new(2.0 * unit(z) - 1.0, 2.0 * unit(z >> 32) - 1.0)
+}
+
+/// Same as [`gen_sdf_shifted`] but the shortcut for pixels far away from
+/// the shape can be turned off, which is only useful for testing the
+/// shortcut.
+fn gen_sdf_with(
+    outline: &GlyphOutline,
+    range: usize,
+    shift: Vector2<f32>,
+    skip_far: bool,
+) -> Result<Bitmap, GenError> {
+    let mut bitmap = Bitmap::new(0, 0, 1, range as f32);
+    gen_sdf_into(&mut bitmap, outline, range, shift, None, skip_far, true)?;
+    Ok(bitmap)
+}
+
+/// Generates the field into the `bitmap`, reusing its buffer.
+///
+/// Every pixel is sampled at its center moved by the `shift` and, if
+/// `jittered` holds a seed and an amount, by the pseudo-random offset of
+/// [`gen_sdf_jittered`]. Distances are only clamped to the range if
+/// `clamped` is `true`, the shortcut for far pixels is skipped otherwise.
+/// The bitmap is left untouched if the generation fails.
+fn gen_sdf_into(
+    bitmap: &mut Bitmap,
+    outline: &GlyphOutline,
+    range: usize,
+    shift: Vector2<f32>,
+    jittered: Option<(u64, f32)>,
+    skip_far: bool,
+    clamped: bool,
+) -> Result<(), GenError> {
     let shape = &outline.shape;
-    let width = outline.width() + outline.offset.x as i32 * 2;
-    let height = outline.height() + outline.offset.y as i32 * 2;
-    let mut data = Vec::new();
-    println!("width: {}, height: {}", width, height);
+    check_range(range)?;
+    let (width, height) = bitmap_size(outline, true)?;
+    let half_range = 0.5 * range as f32;
+    let (skip_far, limit) = if clamped {
+        (skip_far, half_range)
+    } else {
+        (false, f32::INFINITY)
+    };
+    bitmap.width = width;
+    bitmap.height = height;
+    bitmap.channels = 1;
+    bitmap.range = range as f32;
+    bitmap.debug = None;
+    bitmap.to_shape = Affine2::translation(shift.x, shift.y);
+    let distances = &mut bitmap.distances;
+    distances.clear();
+    if shape.is_empty() {
+        distances.resize(width as usize * height as usize, -half_range);
+        return Ok(());
+    }
+    let shape_distance = ShapeDistance::new(shape);
+    // Pixels further than the range from the conservative bounds of the
+    // shape are always clamped to the far outside.
+    let (min, max) = shape.bounds();
+    let (min, max) = (
+        Vector2::new(min.x - half_range, min.y - half_range),
+        Vector2::new(max.x + half_range, max.y + half_range),
+    );
+    // Pixels are visited block by block, so neighbouring pixels measure
+    // the same nearby segments one after another.
+    distances.resize(width as usize * height as usize, 0.0);
+    for (x, y) in tile_order(width, height, TILE_SIZE) {
+        let mut pixel =
+            Vector2::new(x as f32 + 0.5 + shift.x, y as f32 + 0.5 + shift.y);
+        if let Some((seed, amount)) = jittered {
+            let index = y as u64 * width as u64 + x as u64;
+            pixel = pixel + amount * jitter(seed, index);
+        }
+
+        let far = skip_far
+            && (pixel.x < min.x
+                || pixel.y < min.y
+                || pixel.x > max.x
+                || pixel.y > max.y);
+        distances[y as usize * width as usize + x as usize] = if far {
+            -half_range
+        } else {
+            shape_distance.signed_distance(pixel).clamp(-limit, limit)
+        };
+    }
+
+    Ok(())
+}
+
+/// Side of the square blocks of pixels the field is generated in.
+const TILE_SIZE: u32 = 16;
+
+/// Returns the coordinates of all pixels in blocks of `size` by `size`
+/// pixels, going through the blocks and the pixels in every block row by
+/// row. Blocks on the right and bottom edges are cut to the bitmap.
+fn tile_order(
+    width: u32,
+    height: u32,
+    size: u32,
+) -> impl Iterator<Item = (u32, u32)> {
+    let size = size.max(1);
+    (0..height).step_by(size as usize).flat_map(move |top| {
+        (0..width).step_by(size as usize).flat_map(move |left| {
+            (top..(top + size).min(height)).flat_map(move |y| {
+                (left..(left + size).min(width)).map(move |x| (x, y))
+            })
+        })
+    })
+}
+
+/// Same as [`gen_sdf`] but distances are first computed on a coarse grid
+/// of samples `step` pixels apart and only the cells of the grid close to
+/// the edge are refined at the full resolution.
+///
+/// Cells far enough from the edge for the distance to stay out of the
+/// range in the whole cell are filled with the clamped distance of their
+/// corners, which is the same value the full generation would produce.
+pub fn gen_sdf_coarse_to_fine(
+    outline: &GlyphOutline,
+    range: usize,
+    step: u32,
+) -> Result<Bitmap, GenError> {
+    let shape = &outline.shape;
+    check_range(range)?;
+    let (width, height) = bitmap_size(outline, true)?;
+    if shape.is_empty() {
+        return Ok(Bitmap::new(width, height, 1, range as f32));
+    }
+    let shape_distance = ShapeDistance::new(shape);
+    let half_range = 0.5 * range as f32;
+    let step = step.max(1);
+
+    // Unclamped distances at the corners of every cell.
+    let columns = width.div_ceil(step) + 1;
+    let rows = height.div_ceil(step) + 1;
+    let mut coarse = Vec::with_capacity(columns as usize * rows as usize);
+    for j in 0..rows {
+        for i in 0..columns {
+            let sample =
+                Vector2::new((i * step) as f32 + 0.5, (j * step) as f32 + 0.5);
+            coarse.push(shape_distance.signed_distance(sample));
+        }
+    }
+
+    // Distances change at most by the distance travelled, so no pixel in a
+    // cell is closer to the edge than its corners minus half of the cell
+    // diagonal.
+    let margin = half_range + step as f32 * std::f32::consts::FRAC_1_SQRT_2;
+    let mut distances = vec![0.0; width as usize * height as usize];
+    for j in 0..rows - 1 {
+        for i in 0..columns - 1 {
+            let index = (j * columns + i) as usize;
+            let corners = [
+                coarse[index],
+                coarse[index + 1],
+                coarse[index + columns as usize],
+                coarse[index + columns as usize + 1],
+            ];
+            let far = corners.iter().all(|d| d.abs() >= margin)
+                && corners.iter().all(|d| d.signum() == corners[0].signum());
+
+            for y in j * step..((j + 1) * step).min(height) {
+                for x in i * step..((i + 1) * step).min(width) {
+                    let signed_distance = if far {
+                        corners[0]
+                    } else {
+                        let pixel =
+                            Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                        shape_distance.signed_distance(pixel)
+                    };
+                    distances[(y * width + x) as usize] =
+                        signed_distance.clamp(-half_range, half_range);
+                }
+            }
+        }
+    }
+
+    Ok(Bitmap {
+        distances,
+        width,
+        height,
+        channels: 1,
+        range: range as f32,
+        debug: None,
+        to_shape: Affine2::IDENTITY,
+    })
+}
+
+/// Generates a signed distance field of a closed polygon.
+///
+/// The polygon is closed automatically, so the last point doesn't have to
+/// repeat the first one. Points can go in either direction, the inside of
+/// the polygon always has positive distances.
+///
+/// Points and the `bbox` of the generated area are scaled by `scale`.
+///
+/// Fails with [`GenError::InvalidShape`] if there are fewer than three
+/// points.
+pub fn sdf_from_polygon(
+    points: &[Vector2<f32>],
+    mut bbox: BBox,
+    scale: Scale,
+    range: usize,
+) -> Result<Bitmap, GenError> {
+    if points.len() < 3 {
+        return Err(GenError::InvalidShape);
+    }
+
+    // Filled contours go clockwise with the y axis pointing down.
+    let area = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.cross(*b))
+        .sum::<f32>();
+    let mut points = points.to_vec();
+    if area < 0.0 {
+        points.reverse();
+    }
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(points[0].x, points[0].y);
+    for point in points.iter().skip(1).chain(points.first()) {
+        builder.line_to(point.x, point.y);
+    }
+    builder.close();
+    let (shape, _) = builder.build_shape_scaled_with_report(scale);
+    bbox.scale(scale);
+
+    let mut bitmap =
+        GlyphOutline::from_shape(shape, bbox, scale, Offset::uniform(0.0))
+            .generate_sdf(range)?;
+    bitmap.to_shape = Affine2::scale(scale.x.recip(), scale.y.recip());
+    Ok(bitmap)
+}
+
+/// Generates an approximate signed distance field of the area where the
+/// `inside` predicate holds, for sources without an outline.
+///
+/// The predicate is sampled once at the center of every pixel and the
+/// distances are found with a distance transform of the sampled mask, so
+/// they are only accurate to about a pixel. Pixel centers are passed in
+/// bitmap coordinates and the inside has positive distances.
+///
+/// Fails with [`GenError::InvalidRange`] if the `range` is zero and with
+/// [`GenError::TooLarge`] if the `width` or the `height` exceeds
+/// [`DEFAULT_MAX_DIMENSION`].
+pub fn sdf_from_predicate(
+    width: u32,
+    height: u32,
+    range: usize,
+    inside: impl Fn(Vector2<f32>) -> bool,
+) -> Result<Bitmap, GenError> {
+    check_range(range)?;
+    if width > DEFAULT_MAX_DIMENSION || height > DEFAULT_MAX_DIMENSION {
+        return Err(GenError::TooLarge {
+            width: width as u64,
+            height: height as u64,
+        });
+    }
+    let mask = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| inside(Vector2::new(x as f32 + 0.5, y as f32 + 0.5)))
+        .collect::<Vec<_>>();
+    let to_outside = squared_distance_transform(&mask, width, height, false);
+    let to_inside = squared_distance_transform(&mask, width, height, true);
+
+    // The edge lies half way between the centers of neighbouring pixels.
+    let half_range = 0.5 * range as f32;
+    let distances = mask
+        .iter()
+        .zip(to_outside.iter().zip(&to_inside))
+        .map(|(&inside, (&to_outside, &to_inside))| {
+            let distance = if inside {
+                to_outside.sqrt() - 0.5
+            } else {
+                0.5 - to_inside.sqrt()
+            };
+            distance.clamp(-half_range, half_range)
+        })
+        .collect();
+
+    Ok(Bitmap {
+        distances,
+        width,
+        height,
+        channels: 1,
+        range: range as f32,
+        debug: None,
+        to_shape: Affine2::IDENTITY,
+    })
+}
+
+/// Returns the squared distance from every pixel to the nearest pixel of
+/// the `mask` equal to `seed`, or infinity if there is no such pixel.
+fn squared_distance_transform(
+    mask: &[bool],
+    width: u32,
+    height: u32,
+    seed: bool,
+) -> Vec<f32> {
+    let (width, height) = (width as usize, height as usize);
+    let mut distances = mask
+        .iter()
+        .map(|&m| if m == seed { 0.0 } else { f32::INFINITY })
+        .collect::<Vec<_>>();
+
+    // Columns first, then rows of the column results.
+    let mut line = Vec::with_capacity(width.max(height));
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| distances[y * width + x]));
+        for (y, d) in squared_distance_1d(&line).into_iter().enumerate() {
+            distances[y * width + x] = d;
+        }
+    }
+    for row in distances.chunks_mut(width.max(1)) {
+        let transformed = squared_distance_1d(row);
+        row.copy_from_slice(&transformed);
+    }
+
+    distances
+}
+
+/// One dimensional squared distance transform of the sampled function `f`,
+/// the lower envelope of the parabolas rooted at its finite samples.
+fn squared_distance_1d(f: &[f32]) -> Vec<f32> {
+    // Parabolas of the envelope as (root, height, start of its interval).
+    let mut envelope: Vec<(f32, f32, f32)> = Vec::new();
+    for (q, &fq) in f.iter().enumerate().filter(|(_, f)| f.is_finite()) {
+        let q = q as f32;
+        let mut start = f32::NEG_INFINITY;
+        while let Some(&(p, fp, p_start)) = envelope.last() {
+            start = ((fq + q * q) - (fp + p * p)) / (2.0 * (q - p));
+            if start > p_start {
+                break;
+            }
+            envelope.pop();
+            start = f32::NEG_INFINITY;
+        }
+        envelope.push((q, fq, start));
+    }
+    if envelope.is_empty() {
+        return vec![f32::INFINITY; f.len()];
+    }
+
+    let mut current = 0;
+    (0..f.len())
+        .map(|x| {
+            let x = x as f32;
+            while current + 1 < envelope.len() && envelope[current + 1].2 < x {
+                current += 1;
+            }
+            let (p, fp, _) = envelope[current];
+            (x - p) * (x - p) + fp
+        })
+        .collect()
+}
+
+pub fn gen_pseudo_sdf(
+    outline: &GlyphOutline,
+    range: usize,
+) -> Result<Bitmap, GenError> {
+    let shape = &outline.shape;
+    check_range(range)?;
+    let (width, height) = bitmap_size(outline, false)?;
+    if shape.is_empty() {
+        return Ok(Bitmap::new(width, height, 1, range as f32));
+    }
+    let shape_distance = ShapeDistance::new(shape);
+    let half_range = 0.5 * range as f32;
+    let mut distances = Vec::with_capacity(width as usize * height as usize);
     for y in 0..height {
         for x in 0..width {
             let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
 
-            let signed_distance = shortest_distance(shape, pixel).real_signed();
+            let signed_distance = shape_distance.signed_distance(pixel);
 
-            let normalized = (signed_distance / range as f32) + 0.5;
+            distances.push(signed_distance.clamp(-half_range, half_range));
+        }
+    }
 
-            // When f32 is being converted to u8 it is automatically
-            // clamped in range [0, 255].
-            let sdf = (normalized * 255.0) as u8;
+    Ok(Bitmap {
+        distances,
+        width,
+        height,
+        channels: 1,
+        range: range as f32,
+        debug: None,
+        to_shape: Affine2::IDENTITY,
+    })
+}
+
+/// Returns a signed distance field with the antialiasing already applied,
+/// for text rendered at a single size without a smoothstep in the shader.
+///
+/// Distances are put through a smoothstep `smoothing` pixels wide on both
+/// sides of the edge, so [`Bitmap::normalized_distances`] returns the
+/// coverage of every pixel, saturating at `0.0` outside and `1.0` inside.
+pub fn gen_hybrid(
+    outline: &GlyphOutline,
+    range: usize,
+    smoothing: f32,
+) -> Result<Bitmap, GenError> {
+    let mut bitmap = gen_sdf(outline, range)?;
+    let range = bitmap.range;
+    let smoothing = smoothing.max(f32::EPSILON);
+    for d in bitmap.distances.iter_mut() {
+        let t = ((*d + smoothing) / (2.0 * smoothing)).clamp(0.0, 1.0);
+        let coverage = t * t * (3.0 - 2.0 * t);
+        *d = (coverage - 0.5) * range;
+    }
+
+    Ok(bitmap)
+}
+
+/// Returns an RGB bitmap where every pixel has the color of the closest
+/// edge, useful for debugging the edge coloring.
+///
+/// Channels of the edge color are fully inside and the rest are
+/// fully outside.
+pub fn gen_edge_color_map(outline: &GlyphOutline) -> Result<Bitmap, GenError> {
+    let shape = &outline.shape;
+    let (width, height) = bitmap_size(outline, true)?;
+    let colors = color_shape(shape, DEFAULT_ANGLE_THRESHOLD);
+    let shape_distance = ShapeDistance::new(shape);
+
+    let mut distances = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
 
-            data.push(sdf);
+            let color = shape_distance
+                .closest_segment(pixel)
+                .map_or(EdgeColor::Black, |(c, s, _)| colors[c][s]);
+
+            distances
+                .extend(color.channels().map(|on| if on { 0.5 } else { -0.5 }));
         }
     }
 
-    Bitmap {
-        data,
-        width: width as u32,
-        height: height as u32,
+    Ok(Bitmap {
+        distances,
+        width,
+        height,
+        channels: 3,
+        range: 1.0,
+        debug: None,
+        to_shape: Affine2::IDENTITY,
+    })
+}
+
+/// Fails if the range is zero, before anything is allocated.
+pub(crate) fn check_range(range: usize) -> Result<(), GenError> {
+    if range == 0 {
+        Err(GenError::InvalidRange)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the dimensions of the bitmap generated from the outline, with
+/// the outline offset added to every side if `padded` is `true`.
+///
+/// Fails before anything is allocated if a dimension exceeds the maximum
+/// dimension of the outline.
+fn bitmap_size(
+    outline: &GlyphOutline,
+    padded: bool,
+) -> Result<(u32, u32), GenError> {
+    let (pad_x, pad_y) = if padded {
+        let (pad_x, pad_y) = outline.offset.padding();
+        (pad_x as i64 * 2, pad_y as i64 * 2)
+    } else {
+        (0, 0)
+    };
+    // Computed in 64 bits so absurd scales can't overflow.
+    let bbox = outline.bbox;
+    let width = (bbox.br.x as i64 - bbox.tl.x as i64 + pad_x).max(0) as u64;
+    let height = (bbox.br.y as i64 - bbox.tl.y as i64 + pad_y).max(0) as u64;
+
+    let max = outline.max_dimension as u64;
+    if width > max || height > max {
+        return Err(GenError::TooLarge { width, height });
     }
+
+    Ok((width as u32, height as u32))
 }
 
-pub fn gen_pseudo_sdf(outline: &GlyphOutline, range: usize) -> Bitmap {
-    let shape = &outline.shape;
-    let width = outline.width();
-    let height = outline.height();
-    let mut data = Vec::new();
-    println!("width: {}, height: {}", width, height);
-    for y in 0..height as usize {
-        for x in 0..width as usize {
+/// A shape prepared for many distance queries, with the bounds of every
+/// contour computed once.
+///
+/// Contours whose bounds are further away than the closest segment found
+/// so far are culled without computing the distance to their segments.
+/// Culling never changes the result, since a culled contour can't compare
+/// less than the closest segment.
+struct ShapeDistance<'a> {
+    shape: &'a Shape,
+    bounds: Vec<(Vector2<f32>, Vector2<f32>)>,
+}
+
+impl<'a> ShapeDistance<'a> {
+    fn new(shape: &'a Shape) -> Self {
+        let bounds = shape.contours.iter().map(|c| c.bounds()).collect();
+        Self { shape, bounds }
+    }
+
+    /// Returns the distance to the closest segment, positive inside of the
+    /// shape.
+    ///
+    /// The sign comes from the winding number, so it doesn't depend on the
+    /// order or overlaps of the contours.
+    fn signed_distance(&self, pixel: Vector2<f32>) -> f32 {
+        let distance = self
+            .closest_segment(pixel)
+            .map_or(Distance::FAR, |(_, _, distance)| distance);
+        let sign = if self.winding_number(pixel) != 0 {
+            1.0
+        } else {
+            -1.0
+        };
+        sign * distance.real_dist
+    }
+
+    /// Returns the same winding number as [`Shape::winding_number`].
+    ///
+    /// The ray going from the pixel in the positive x direction can only
+    /// cross contours whose bounds span the pixel vertically and reach to
+    /// the right of it, so the other contours are skipped.
+    fn winding_number(&self, pixel: Vector2<f32>) -> i32 {
+        self.shape
+            .contours
+            .iter()
+            .zip(self.bounds.iter())
+            .filter(|(_, (min, max))| {
+                min.y <= pixel.y && pixel.y <= max.y && pixel.x <= max.x
+            })
+            .map(|(contour, _)| contour.winding_number(pixel))
+            .sum()
+    }
+
+    /// Returns the index of the contour and the index of the segment in it
+    /// closest to the pixel together with the [`Distance`] to it.
+    ///
+    /// Segments are compared the same way as in [`Shape::closest_feature`].
+    fn closest_segment(
+        &self,
+        pixel: Vector2<f32>,
+    ) -> Option<(usize, usize, Distance)> {
+        let mut closest: Option<(usize, usize, Distance)> = None;
+        for (c, contour) in self.shape.contours.iter().enumerate() {
+            if let Some((_, _, best)) = closest {
+                // Distances within the tolerance of `Distance::partial_cmp`
+                // are compared by the orthogonality, so the contour is only
+                // skipped if it's further away even with the tolerance.
+                let (min, max) = self.bounds[c];
+                let outside = Vector2::new(
+                    (min.x - pixel.x).max(pixel.x - max.x).max(0.0),
+                    (min.y - pixel.y).max(pixel.y - max.y).max(0.0),
+                );
+                if outside.magnitude() * (1.0 - Distance::EPSILON)
+                    > best.real_dist
+                {
+                    continue;
+                }
+            }
+            if let Some((s, distance)) = contour.closest_segment(pixel) {
+                closest = match closest {
+                    Some(best) if best.2 < distance => Some(best),
+                    _ => Some((c, s, distance)),
+                };
+            }
+        }
+        closest
+    }
+}
+
+/// Creates an outline of a filled rectangle in the middle of a
+/// `size` x `size` bitmap, leaving `margin` pixels free on every side.
+#[cfg(test)]
+pub(crate) fn rect_outline(size: u32, margin: f32) -> GlyphOutline {
+    use crate::{font::BBox, path::PathBuilder, Offset, Scale};
+
+    let far = size as f32 - margin;
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(margin, margin);
+    builder.line_to(far, margin);
+    builder.line_to(far, far);
+    builder.line_to(margin, far);
+    builder.line_to(margin, margin);
+    builder.close();
+
+    let bbox =
+        BBox::new(Vector2::ZERO_I32, Vector2::new(size as i32, size as i32));
+    GlyphOutline::from_shape(
+        builder.build_shape(),
+        bbox,
+        Scale::uniform(1.0),
+        Offset::uniform(0.0),
+    )
+}
+
+/// Adds a closed square contour with the corners `(min, min)` and
+/// `(max, max)` to the `builder`, going clockwise if `cw` is `true`.
+#[cfg(test)]
+pub(crate) fn square_contour(
+    builder: &mut crate::path::PathBuilder,
+    min: f32,
+    max: f32,
+    cw: bool,
+) {
+    rect_contour(builder, (min, min), (max, max), cw);
+}
+
+/// Adds a closed rectangle contour with the corners `min` and `max` to the
+/// `builder`, going clockwise if `cw` is `true`.
+#[cfg(test)]
+pub(crate) fn rect_contour(
+    builder: &mut crate::path::PathBuilder,
+    min: (f32, f32),
+    max: (f32, f32),
+    cw: bool,
+) {
+    let mut points = [min, (max.0, min.1), max, (min.0, max.1)];
+    if !cw {
+        points.reverse();
+    }
+    builder.open_at(points[0].0, points[0].1);
+    for &(x, y) in points.iter().skip(1).chain(points.first()) {
+        builder.line_to(x, y);
+    }
+    builder.close();
+}
+
+#[test]
+fn from_raw_test() {
+    let data = vec![-1.0, 0.5, 2.0, -0.25, 0.0, 1.5];
+    let bitmap = Bitmap::from_raw(3, 2, 1, data.clone()).unwrap();
+    assert_eq!((bitmap.width, bitmap.height, bitmap.channels), (3, 2, 1));
+    assert_eq!(bitmap.distances(), data);
+    assert_eq!(bitmap.distance(2, 0), 2.0);
+    assert_eq!(bitmap.distance(0, 1), -0.25);
+    assert_eq!(bitmap.range(), 4.0);
+
+    let rgb = Bitmap::from_raw(1, 2, 3, data.clone()).unwrap();
+    assert_eq!(rgb.pixel(0, 1), [-0.25, 0.0, 1.5]);
+
+    assert_eq!(
+        Bitmap::from_raw(2, 2, 1, data).unwrap_err(),
+        GenError::SizeMismatch {
+            expected: 4,
+            actual: 6
+        }
+    );
+    assert!(matches!(
+        Bitmap::from_raw(usize::MAX, 2, 1, Vec::new()),
+        Err(GenError::TooLarge { .. })
+    ));
+}
+
+#[test]
+fn blit_test() {
+    let src = Bitmap::from_raw(2, 2, 1, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    let mut dst = Bitmap::new(4, 3, 1, 4.0);
+    dst.blit(&src, 1, 1);
+    assert_eq!(
+        dst.distances(),
+        [
+            -2.0, -2.0, -2.0, -2.0, //
+            -2.0, 1.0, 2.0, -2.0, //
+            -2.0, 3.0, 4.0, -2.0,
+        ]
+    );
+
+    // Channels of a pixel are copied together.
+    let rgb = Bitmap::from_raw(1, 1, 3, vec![0.5, 1.0, 1.5]).unwrap();
+    let mut dst = Bitmap::new(2, 1, 3, 4.0);
+    dst.blit(&rgb, 1, 0);
+    assert_eq!(dst.pixel(1, 0), [0.5, 1.0, 1.5]);
+    assert_eq!(dst.pixel(0, 0), [-2.0; 3]);
+}
+
+#[test]
+fn stroke_test() {
+    let sdf = rect_outline(32, 4.0).generate_sdf(8).unwrap();
+    let stroked = sdf.stroke(1.5);
+
+    // Far inside of the rectangle turns into the outside.
+    assert!(sdf.distance(16, 16) > 0.0);
+    assert!(stroked.distance(16, 16) < 0.0);
+    // Pixels next to the edge are inside the stroke, on both sides.
+    assert!(stroked.distance(4, 16) > 0.0);
+    assert!(stroked.distance(3, 16) > 0.0);
+    // Far outside stays outside.
+    assert!(stroked.distance(0, 0) < 0.0);
+}
+
+#[test]
+fn downscale_sdf_test() {
+    let large = gen_sdf(&rect_outline(64, 16.0), 16).unwrap();
+    let small = large.downscale_sdf(4);
+    assert_eq!((small.width, small.height, small.range), (16, 16, 4.0));
+
+    // The same rectangle generated directly at the smaller size.
+    let direct = gen_sdf(&rect_outline(16, 4.0), 4).unwrap();
+    // Signs match and the zero crossings stay in place, even around the
+    // corners.
+    for (a, b) in small.distances.iter().zip(direct.distances.iter()) {
+        assert_eq!(a.signum(), b.signum());
+        assert!((a - b).abs() < 0.15);
+    }
+    assert_eq!(small.pixel_to_shape(4.0, 4.0), Vector2::new(16.0, 16.0));
+}
+
+#[test]
+fn regenerate_from_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let build = |c| {
+        font.glyph(c)
+            .build(crate::Scale::uniform(24.0), Offset::uniform(2.0))
+    };
+
+    let mut bitmap = build('W').generate_sdf(4).unwrap();
+    let buffer = bitmap.distances.as_ptr();
+    for c in ['o', 'i', ' '] {
+        let outline = build(c);
+        bitmap.regenerate_from(&outline, 4).unwrap();
+        let fresh = outline.generate_sdf(4).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (fresh.width, fresh.height));
+        assert_eq!(bitmap.distances, fresh.distances);
+        // Smaller glyphs than the first one fit into the same buffer.
+        assert_eq!(bitmap.distances.as_ptr(), buffer);
+    }
+
+    // A failed generation keeps the previous field.
+    let mut outline = build('W');
+    outline.set_max_dimension(8);
+    assert!(bitmap.regenerate_from(&outline, 4).is_err());
+    assert_eq!(
+        bitmap.distances,
+        build(' ').generate_sdf(4).unwrap().distances
+    );
+}
+
+#[test]
+fn invalid_range_test() {
+    let outline = rect_outline(16, 2.0);
+    assert!(matches!(
+        outline.generate_sdf(0),
+        Err(GenError::InvalidRange)
+    ));
+    assert!(matches!(
+        outline.generate_pseudo_sdf(0),
+        Err(GenError::InvalidRange)
+    ));
+    assert!(matches!(
+        gen_sdf_coarse_to_fine(&outline, 0, 4),
+        Err(GenError::InvalidRange)
+    ));
+
+    let mut bitmap = outline.generate_sdf(1).unwrap();
+    assert!(bitmap.normalized_distances().iter().all(|d| d.is_finite()));
+    assert!(bitmap.regenerate_from(&outline, 0).is_err());
+    assert_eq!(bitmap.range, 1.0);
+}
+
+#[test]
+fn dilate_erode_test() {
+    let sdf = rect_outline(32, 8.0).generate_sdf(8).unwrap();
+    let mut morphed = sdf.clone();
+
+    morphed.dilate(2.0);
+    assert!(morphed.distance(6, 16) > 0.0);
+    assert!(sdf.distance(6, 16) < 0.0);
+
+    morphed.erode(2.0);
+    for (original, restored) in sdf.distances().iter().zip(morphed.distances())
+    {
+        // Saturated distances can't be restored.
+        if original.abs() <= 4.0 - 2.0 {
+            assert!((original - restored).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn sanitize_test() {
+    let mut sdf = rect_outline(16, 4.0).generate_sdf(8).unwrap();
+    assert_eq!(sdf.sanitize(), 0);
+
+    let index = (8 * sdf.width + 4) as usize;
+    let original = sdf.distances[index];
+    sdf.distances[index] = f32::NAN;
+    sdf.distances[0] = f32::INFINITY;
+
+    assert_eq!(sdf.sanitize(), 2);
+    assert!(sdf.distances().iter().all(|d| d.is_finite()));
+    // Neighbours are close to the edge, so is their average.
+    assert!((sdf.distances[index] - original).abs() < 1.0);
+
+    // Replaced neighbours aren't averaged again, whichever comes first.
+    let mut row = Bitmap::new(3, 1, 1, 8.0);
+    row.distances = vec![f32::NAN, f32::NAN, 2.0];
+    assert_eq!(row.sanitize(), 2);
+    assert_eq!(row.distances, [-4.0, 2.0, 2.0]);
+
+    // A pixel counts once no matter how many of its channels are replaced.
+    let mut multi = Bitmap::new(2, 1, 3, 8.0);
+    multi.distances = vec![f32::NAN, f32::INFINITY, 1.0, 1.0, 2.0, 3.0];
+    assert_eq!(multi.sanitize(), 1);
+    assert_eq!(multi.distances, [1.0, 2.0, 1.0, 1.0, 2.0, 3.0]);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn image_conversion_test() {
+    let sdf = rect_outline(24, 4.0).generate_sdf(8).unwrap();
+
+    let gray = sdf.to_gray_image();
+    assert_eq!(gray.dimensions(), (sdf.width, sdf.height));
+    assert_eq!(gray.as_raw(), &sdf.data());
+
+    let rgb = sdf.to_rgb_image();
+    assert_eq!(rgb.dimensions(), (sdf.width, sdf.height));
+    assert_eq!(rgb.get_pixel(12, 12).0, [gray.get_pixel(12, 12).0[0]; 3]);
+}
+
+#[test]
+fn edge_color_map_test() {
+    let outline = rect_outline(32, 4.0);
+    let colors = crate::coloring::color_contour(
+        &outline.shape.contours[0],
+        DEFAULT_ANGLE_THRESHOLD,
+    );
+    let map = outline.generate_edge_color_map().unwrap();
+    assert_eq!(map.channels, 3);
+
+    let expected = |color: EdgeColor| {
+        color.channels().map(|on| if on { 0.5 } else { -0.5 })
+    };
+    // Pixels close to the middle of the top, right, bottom and left edge.
+    let samples = [(16, 5), (26, 16), (16, 26), (5, 16)];
+    for (i, (x, y)) in samples.into_iter().enumerate() {
+        assert_eq!(map.pixel(x, y), expected(colors[i]));
+    }
+    // Adjacent regions meet at the corners with different colors.
+    for i in 0..samples.len() {
+        assert_ne!(colors[i], colors[(i + 1) % samples.len()]);
+    }
+}
+
+#[test]
+fn channels_test() {
+    let rgb = rect_outline(16, 4.0).generate_edge_color_map().unwrap();
+    let split = (0..3).map(|i| rgb.channel(i)).collect::<Vec<_>>();
+    assert!(split.iter().all(|b| b.channels == 1));
+    assert_eq!(split[1].distance(3, 5), rgb.pixel(3, 5)[1]);
+
+    let merged = Bitmap::merge_channels(&split);
+    assert_eq!(merged.channels, 3);
+    assert_eq!(merged.distances, rgb.distances);
+
+    // Multi-channel bitmaps keep their channels together.
+    let merged = Bitmap::merge_channels(&[split[2].clone(), rgb.clone()]);
+    assert_eq!(merged.channels, 4);
+    assert_eq!(merged.pixel(7, 2)[1..], *rgb.pixel(7, 2));
+}
+
+#[test]
+fn polarity_test() {
+    let sdf = rect_outline(16, 4.0).generate_sdf(4).unwrap();
+    let index = (8 * sdf.width + 8) as usize;
+    assert_eq!(sdf.distance(8, 8), 2.0);
+
+    let positive = sdf.normalized_distances_with(Polarity::InsidePositive);
+    assert_eq!(positive[index], 1.0);
+    assert_eq!(positive, sdf.normalized_distances());
+    assert_eq!(sdf.data_with(Polarity::InsidePositive)[index], 255);
+
+    let negative = sdf.normalized_distances_with(Polarity::InsideNegative);
+    assert_eq!(negative[index], 0.0);
+    assert_eq!(negative[0], 1.0);
+    assert_eq!(sdf.data_with(Polarity::InsideNegative)[index], 0);
+}
+
+#[test]
+fn debug_windings_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = crate::Offset::uniform(2.0);
+    let mut outline =
+        font.glyph('o').build(crate::Scale::uniform(32.0), offset);
+    let sdf = outline.generate_sdf_debug(4).unwrap();
+    assert!(outline.generate_sdf(4).unwrap().debug().is_none());
+
+    let windings = &sdf.debug().unwrap().windings;
+    assert_eq!(windings.len(), 2);
+    assert_eq!(*windings, outline.shape.normalize_windings());
+    // The outer contour fills and the inner one cuts out the hole.
+    assert_ne!(windings[0], windings[1]);
+}
+
+#[test]
+fn ink_ratio_test() {
+    let empty = Bitmap::new(8, 8, 1, 4.0);
+    assert_eq!(empty.ink_ratio(0.0), 0.0);
+
+    // The distance of every pixel is within the stroke half width.
+    let filled = empty.stroke(4.0);
+    assert_eq!(filled.ink_ratio(0.0), 1.0);
+
+    // A quarter of the 16x16 tile is inside the 8x8 rectangle.
+    let sdf = rect_outline(16, 4.0).generate_sdf(4).unwrap();
+    assert_eq!(sdf.ink_ratio(0.0), 0.25);
+    assert!(sdf.ink_ratio(1.0) < 0.25);
+}
+
+#[test]
+fn pixel_to_shape_test() {
+    let outline = rect_outline(16, 4.0);
+    let shifted = outline
+        .generate_sdf_offset(4, Vector2::new(0.25, -0.5))
+        .unwrap();
+    // The center of a pixel is where its distance was measured.
+    let center = shifted.pixel_to_shape(4.5, 6.5);
+    assert_eq!(center, Vector2::new(4.75, 6.0));
+    let (_, distance) = outline.shape.closest_point(center);
+    assert!((shifted.distance(4, 6) - distance).abs() < 1e-5);
+
+    let triangle = [
+        Vector2::new(1.0, 1.0),
+        Vector2::new(7.0, 1.0),
+        Vector2::new(4.0, 7.0),
+    ];
+    let bbox = BBox::new(Vector2::new(0, 0), Vector2::new(8, 8));
+    let polygon =
+        sdf_from_polygon(&triangle, bbox, Scale::uniform(2.0), 4).unwrap();
+    assert_eq!(polygon.pixel_to_shape(8.0, 2.0), Vector2::new(4.0, 1.0));
+
+    for bitmap in [&shifted, &polygon] {
+        for (x, y) in [(0.0, 0.0), (3.5, 12.25), (-2.0, 7.75)] {
+            let shape = bitmap.pixel_to_shape(x, y);
+            let pixel = bitmap.shape_to_pixel(shape.x, shape.y);
+            assert!((pixel - Vector2::new(x, y)).magnitude() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn sign_consistency_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = crate::Offset::uniform(4.0);
+    let outline = font.glyph('o').build(crate::Scale::uniform(48.0), offset);
+    let mut sdf = outline.generate_sdf(6).unwrap();
+    assert_eq!(sdf.check_sign_consistency(), Ok(()));
+
+    // Flip a pixel far outside, next to the border, and one in the middle
+    // of the hole which isn't connected to the outside.
+    let index = |x: u32, y: u32| (y * sdf.width + x) as usize;
+    let (cx, cy) = (sdf.width / 2, sdf.height / 2);
+    assert!(sdf.distance(cx, cy) < 0.0);
+    sdf.distances[index(1, 2)] = 3.0;
+    sdf.distances[index(cx, cy)] = 3.0;
+    assert_eq!(sdf.check_sign_consistency(), Err(vec![(1, 2)]));
+}
+
+#[test]
+fn cubic_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Cantarell-VF.otf"
+    ));
+    let offset = crate::Offset::uniform(2.0);
+    for c in ['o', 'S', '&'] {
+        let outline = font.glyph(c).build(crate::Scale::uniform(32.0), offset);
+        let sdf = outline.generate_sdf(4).unwrap();
+
+        // The same outline made of short lines gives nearly the same field.
+        let flat = GlyphOutline::from_shape(
+            outline.shape.flatten(0.01),
+            outline.bbox,
+            outline.scale,
+            outline.offset,
+        );
+        let expected = flat.generate_sdf(4).unwrap();
+        for (a, b) in sdf.distances.iter().zip(&expected.distances) {
+            assert!((a - b).abs() < 0.05);
+        }
+
+        assert!(outline.generate_pseudo_sdf(4).is_ok());
+        assert!(outline.generate_edge_color_map().is_ok());
+    }
+}
+
+#[test]
+fn dimensions_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    for offset in [0.0, 2.0, 2.5, 3.7] {
+        for c in ['A', 'g', 'j', 'W', '.', '@'] {
+            let outline = font
+                .glyph(c)
+                .build(crate::Scale::uniform(33.3), Offset::uniform(offset));
+            let sdf = outline.generate_sdf(4).unwrap();
+            let padding = 2 * offset.ceil() as i32;
+            assert_eq!(sdf.width as i32, outline.width() + padding);
+            assert_eq!(sdf.height as i32, outline.height() + padding);
+
+            // The outline keeps at least the offset from every border.
+            let (min, max) = outline.shape.bounds();
+            assert!(min.x >= offset && min.y >= offset);
+            assert!(sdf.width as f32 - max.x >= offset - 1e-3);
+            assert!(sdf.height as f32 - max.y >= offset - 1e-3);
+        }
+    }
+}
+
+#[test]
+fn max_dimension_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let offset = crate::Offset::uniform(2.0);
+    let outline = font.glyph('W').build(crate::Scale::uniform(1e6), offset);
+
+    match outline.generate_sdf(8) {
+        Err(GenError::TooLarge { width, height }) => {
+            assert!(width > DEFAULT_MAX_DIMENSION as u64);
+            assert!(height > DEFAULT_MAX_DIMENSION as u64);
+        }
+        other => panic!("Expected a too large error, got {:?}", other),
+    }
+
+    // A lower limit also rejects regular sizes.
+    let mut outline =
+        font.glyph('W').build(crate::Scale::uniform(64.0), offset);
+    assert!(outline.generate_sdf(8).is_ok());
+    outline.set_max_dimension(16);
+    assert!(outline.generate_sdf(8).is_err());
+}
+
+#[test]
+fn diff_test() {
+    let sdf = rect_outline(24, 4.0).generate_sdf(8).unwrap();
+    assert_eq!(sdf.diff(&sdf), BitmapDiff::default());
+
+    let mut dilated = sdf.clone();
+    dilated.dilate(1.0);
+    let diff = sdf.diff(&dilated);
+    assert_eq!(diff.max_abs, 1.0);
+    assert!(diff.mean_abs > 0.0 && diff.mean_abs <= 1.0);
+    // Only the pixels which were already clamped to the range stay.
+    assert!(diff.mismatched_pixels > 0);
+    assert!(diff.mismatched_pixels < (sdf.width * sdf.height) as usize);
+}
+
+#[test]
+fn skip_far_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/monserat.ttf"
+    ));
+    for c in ['@', 'i', '.'] {
+        let outline = font
+            .glyph(c)
+            .build(crate::Scale::uniform(32.0), crate::Offset::uniform(12.0));
+
+        let shift = Vector2::new(0.0, 0.0);
+        let fast = gen_sdf_with(&outline, 4, shift, true).unwrap();
+        let full = gen_sdf_with(&outline, 4, shift, false).unwrap();
+        assert_eq!(fast.diff(&full).mismatched_pixels, 0);
+    }
+}
+
+#[test]
+fn quantize_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let sdf = font
+        .glyph('@')
+        .build(crate::Scale::uniform(32.0), Offset::uniform(4.0))
+        .generate_sdf(8)
+        .unwrap();
+    let bytes = sdf.quantize(8, 8.0);
+    assert_eq!(
+        bytes,
+        sdf.data().into_iter().map(u16::from).collect::<Vec<_>>()
+    );
+
+    // Every 8-bit step is split into many 16-bit steps.
+    let distinct = |values: &[u16]| {
+        values
+            .iter()
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    };
+    let words = sdf.quantize(16, 8.0);
+    assert!(distinct(&words) > distinct(&bytes));
+    for (word, byte) in words.iter().zip(bytes.iter()) {
+        assert!((*word as f32 / 257.0 - *byte as f32).abs() <= 1.0);
+    }
+
+    // Distances closer than one 8-bit step still differ in 16 bits.
+    let close = Bitmap {
+        distances: vec![0.0, 0.01],
+        ..Bitmap::new(2, 1, 1, 8.0)
+    };
+    let bytes = close.quantize(8, 8.0);
+    let words = close.quantize(16, 8.0);
+    assert_eq!(bytes[0], bytes[1]);
+    assert!(words[0] < words[1]);
+}
+
+#[test]
+fn max_interior_distance_test() {
+    let thick = max_interior_distance(&rect_outline(24, 2.0));
+    let thin = max_interior_distance(&rect_outline(8, 2.0));
+    // Half of the sides of 20 and 4 pixels, sampled next to the centers.
+    let half_diagonal = std::f32::consts::FRAC_1_SQRT_2;
+    assert!(thick <= 10.0 && thick >= 10.0 - half_diagonal);
+    assert!(thin <= 2.0 && thin >= 2.0 - half_diagonal);
+
+    // A field with this range keeps the center of the thick square apart
+    // from the rest of its inside.
+    let range = (2.0 * thick).ceil() as usize + 1;
+    let sdf = rect_outline(24, 2.0).generate_sdf(range).unwrap();
+    assert!(sdf.distances.iter().all(|d| d.abs() < 0.5 * range as f32));
+
+    // Cubic outlines are measured too.
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Cantarell-VF.otf"
+    ));
+    let scale = crate::Scale::uniform(64.0);
+    let bold = font.glyph('.').build(scale, Offset::uniform(2.0));
+    let thin = font.glyph('l').build(scale, Offset::uniform(2.0));
+    assert!(max_interior_distance(&bold) > max_interior_distance(&thin));
+    assert!(max_interior_distance(&thin) > 0.0);
+}
+
+#[test]
+fn tiled_order_test() {
+    // Every pixel is visited exactly once, even in cut blocks.
+    let mut visited = tile_order(37, 21, TILE_SIZE).collect::<Vec<_>>();
+    assert_eq!(visited.len(), 37 * 21);
+    assert_eq!(&visited[..3], [(0, 0), (1, 0), (2, 0)]);
+    assert_eq!(visited[TILE_SIZE as usize], (0, 1));
+    visited.sort_by_key(|&(x, y)| (y, x));
+    let rows = (0..21).flat_map(|y| (0..37).map(move |x| (x, y)));
+    assert!(visited.into_iter().eq(rows));
+
+    // Measuring the pixels row by row has to give the same field.
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/monserat.ttf"
+    ));
+    for c in ['@', 'B', 'i'] {
+        let outline = font
+            .glyph(c)
+            .build(crate::Scale::uniform(48.0), crate::Offset::uniform(3.0));
+        let tiled = gen_sdf(&outline, 6).unwrap();
+        assert!(tiled.width > TILE_SIZE && tiled.height > TILE_SIZE);
+        let shape_distance = ShapeDistance::new(&outline.shape);
+        for y in 0..tiled.height {
+            for x in 0..tiled.width {
+                let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let expected =
+                    shape_distance.signed_distance(pixel).clamp(-3.0, 3.0);
+                assert_eq!(tiled.distance(x, y), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn contour_culling_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/monserat.ttf"
+    ));
+    for c in ['@', 'B', '%', 'i'] {
+        let outline = font
+            .glyph(c)
+            .build(crate::Scale::uniform(32.0), crate::Offset::uniform(4.0));
+        let culled = ShapeDistance::new(&outline.shape);
+        // Bounds covering the whole plane never cull a contour.
+        let mut full = ShapeDistance::new(&outline.shape);
+        let everywhere = (
+            Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            Vector2::new(f32::INFINITY, f32::INFINITY),
+        );
+        full.bounds.fill(everywhere);
+
+        let (width, height) = bitmap_size(&outline, true).unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let (c1, s1, d1) = culled.closest_segment(pixel).unwrap();
+                let (c2, s2, d2) = full.closest_segment(pixel).unwrap();
+                assert_eq!((c1, s1), (c2, s2));
+                assert_eq!(d1.real_dist, d2.real_dist);
+                assert_eq!(
+                    culled.winding_number(pixel),
+                    outline.shape.winding_number(pixel)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn unclamped_test() {
+    let outline = rect_outline(32, 12.0);
+    let clamped = gen_sdf(&outline, 4).unwrap();
+    let unclamped = gen_sdf_unclamped(&outline, 4).unwrap();
+
+    // The corner pixel is 11.5 pixels away from the rectangle diagonally.
+    let far = 11.5 * std::f32::consts::SQRT_2;
+    assert_eq!(clamped.distance(0, 0), -2.0);
+    assert!((unclamped.distance(0, 0) + far).abs() < 1e-4);
+    // The middle of the 8 pixels wide rectangle is further inside than
+    // the range reaches too.
+    assert_eq!(clamped.distance(16, 16), 2.0);
+    assert!((unclamped.distance(16, 16) - 3.5).abs() < 1e-4);
+
+    // Inside of the range both fields are the same.
+    for (a, b) in unclamped.distances().iter().zip(clamped.distances()) {
+        if b.abs() < 2.0 {
+            assert_eq!(a, b);
+        }
+    }
+}
+
+#[test]
+fn jittered_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let amount = 0.05;
+    // Distances change at most by the distance the samples moved.
+    let tolerance = 2.0 * amount * std::f32::consts::SQRT_2 + 1e-3;
+    for c in ['@', 'g', 'W'] {
+        let outline = font
+            .glyph(c)
+            .build(crate::Scale::uniform(32.0), Offset::uniform(2.0));
+        let a = gen_sdf_jittered(&outline, 4, 1, amount).unwrap();
+        let b = gen_sdf_jittered(&outline, 4, 2, amount).unwrap();
+        let again = gen_sdf_jittered(&outline, 4, 1, amount).unwrap();
+        assert_eq!(a.distances, again.distances);
+        assert_ne!(a.distances, b.distances);
+
+        for (a, b) in a.distances.iter().zip(b.distances.iter()) {
+            if a.abs() > tolerance && b.abs() > tolerance {
+                assert!((a - b).abs() <= tolerance, "{} vs {}", a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn predicate_test() {
+    let center = Vector2::new(16.0, 16.0);
+    let sdf = sdf_from_predicate(32, 32, 8, |point| {
+        (point - center).magnitude() < 8.0
+    })
+    .unwrap();
+    assert_eq!((sdf.width, sdf.height, sdf.channels), (32, 32, 1));
+
+    for y in 0..32 {
+        for x in 0..32 {
             let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let exact = 8.0 - (pixel - center).magnitude();
+            let distance = sdf.distances[y * 32 + x];
+            assert_eq!(distance > 0.0, exact > 0.0);
+            assert!((distance - exact.clamp(-4.0, 4.0)).abs() <= 1.0);
+        }
+    }
+    assert_eq!(sdf.distances[16 * 32 + 16], 4.0);
+    assert_eq!(sdf.distances[0], -4.0);
 
-            let signed_distance = shortest_distance(shape, pixel).real_signed();
+    // Without any inside pixel everything is as far out as possible.
+    let empty = sdf_from_predicate(4, 3, 2, |_| false).unwrap();
+    assert!(empty.distances.iter().all(|&d| d == -1.0));
 
-            let normalized = (signed_distance / range as f32) + 0.5;
+    let result = sdf_from_predicate(4, 3, 0, |_| true);
+    assert!(matches!(result, Err(GenError::InvalidRange)));
 
-            // When f32 is being converted to u8 it is automatically
-            // clamped in range [0, 255].
-            let pseudo = (normalized * 255.0) as u8;
+    // Fails before the predicate is sampled.
+    let result = sdf_from_predicate(u32::MAX, 1, 2, |_| unreachable!());
+    assert!(matches!(
+        result,
+        Err(GenError::TooLarge {
+            width: 4294967295,
+            height: 1
+        })
+    ));
+}
+
+#[test]
+fn mask_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('&')
+        .build(crate::Scale::uniform(32.0), Offset::uniform(2.0));
+    let (sdf, mask) = gen_sdf_with_mask(&outline, 4).unwrap();
+    assert_eq!(sdf.distances, outline.generate_sdf(4).unwrap().distances);
+    assert_eq!((mask.width, mask.height), (sdf.width, sdf.height));
+
+    // Inside pixels of the mask are exactly the positive pixels of the
+    // field.
+    let coverage = mask.normalized_distances();
+    for (covered, distance) in coverage.iter().zip(sdf.distances.iter()) {
+        assert_eq!(*covered == 1.0, *distance > 0.0);
+        assert!(*covered == 1.0 || *covered == 0.0);
+    }
+    assert!(coverage.contains(&1.0) && coverage.contains(&0.0));
+}
+
+#[test]
+fn coarse_to_fine_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/monserat.ttf"
+    ));
+    for c in ['@', 'i', 'W'] {
+        let outline = font
+            .glyph(c)
+            .build(crate::Scale::uniform(48.0), crate::Offset::uniform(6.0));
 
-            data.push(pseudo);
+        let full = outline.generate_sdf(4).unwrap();
+        let coarse = gen_sdf_coarse_to_fine(&outline, 4, 4).unwrap();
+        assert_eq!((coarse.width, coarse.height), (full.width, full.height));
+        for (a, b) in coarse.distances.iter().zip(full.distances.iter()) {
+            if b.abs() == 2.0 {
+                assert_eq!(a, b);
+            } else {
+                assert!((a - b).abs() < 1e-4);
+            }
         }
     }
+}
+
+#[test]
+fn shifted_test() {
+    let outline = rect_outline(16, 4.0);
+    let sdf = outline.generate_sdf(8).unwrap();
+    let shifted = outline
+        .generate_sdf_offset(8, Vector2::new(0.5, 0.0))
+        .unwrap();
 
-    Bitmap {
-        data,
-        width: width as u32,
-        height: height as u32,
+    // The left edge is at x = 4, so the zero-crossing moves from the
+    // border between pixels 3 and 4 to the center of pixel 3.
+    assert!((sdf.distance(3, 8) + 0.5).abs() < 1e-4);
+    assert!(shifted.distance(3, 8).abs() < 1e-4);
+    for x in 1..6 {
+        let expected = sdf.distance(x, 8) + 0.5;
+        assert!((shifted.distance(x, 8) - expected).abs() < 1e-4);
     }
 }
 
-/// Returns [`Distance`]
-fn shortest_distance(shape: &Shape, pixel: Vector2<f32>) -> Distance {
-    shape
-        .contours
-        .iter()
-        .map(|contour| contour.distance(pixel))
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .expect("Error: There are no distances??")
+#[test]
+fn polygon_test() {
+    let bbox = BBox::new(Vector2::ZERO_I32, Vector2::new(16, 16));
+    let triangle = [
+        Vector2::new(2.0, 2.0),
+        Vector2::new(14.0, 2.0),
+        Vector2::new(8.0, 14.0),
+    ];
+    let sdf =
+        sdf_from_polygon(&triangle, bbox, Scale::uniform(2.0), 8).unwrap();
+    assert_eq!((sdf.width, sdf.height), (32, 32));
+    assert!(sdf.distance(16, 12) > 0.0);
+    assert!(sdf.distance(1, 30) < 0.0);
+
+    // The direction of the points doesn't matter.
+    let mut reversed = triangle;
+    reversed.reverse();
+    let other =
+        sdf_from_polygon(&reversed, bbox, Scale::uniform(2.0), 8).unwrap();
+    assert!(sdf.diff(&other).max_abs < 1e-4);
+
+    assert_eq!(
+        sdf_from_polygon(&triangle[..2], bbox, Scale::uniform(2.0), 8)
+            .unwrap_err(),
+        GenError::InvalidShape
+    );
+}
+
+#[test]
+fn texel_distance_test() {
+    // The left edge is half a pixel right of a pixel boundary, so the
+    // center of the pixel at `x = 5` is exactly one texel inside.
+    let sdf = rect_outline(32, 4.5).generate_sdf(8).unwrap();
+    assert!((sdf.distance(5, 16) - 1.0).abs() < 1e-4);
+    assert!((sdf.distance(3, 16) + 1.0).abs() < 1e-4);
+
+    let normalized = sdf.normalized_distances();
+    let value = normalized[(16 * sdf.width + 5) as usize];
+    assert!(((value - 0.5) * sdf.range() - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn hybrid_test() {
+    let outline = rect_outline(32, 8.0);
+    let hybrid = outline.generate_hybrid(8, 1.0).unwrap();
+    let coverage = hybrid.normalized_distances();
+    let at = |x: u32, y: u32| coverage[(y * hybrid.width + x) as usize];
+
+    // Interior and exterior saturate.
+    assert_eq!(at(16, 16), 1.0);
+    assert_eq!(at(2, 16), 0.0);
+    assert_eq!(at(6, 16), 0.0);
+    // Pixels at the edge are partially covered.
+    let (outside, inside) = (at(7, 16), at(8, 16));
+    assert!(outside > 0.0 && outside < 0.5);
+    assert!(inside > 0.5 && inside < 1.0);
+    assert!((outside + inside - 1.0).abs() < 1e-4);
 }
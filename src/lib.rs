@@ -1,3 +1,5 @@
+mod atlas;
+mod coloring;
 mod font;
 mod gen;
 mod math;
@@ -6,8 +8,19 @@ mod path;
 mod shape;
 mod vector;
 
+pub use atlas::{AtlasBuilder, AtlasError, AtlasGlyph, AtlasLayout};
+pub use coloring::EdgeColor;
 pub use font::*;
-pub use path::ShapeBuilder;
-pub use vector::Vector2;
+pub use gen::{
+    sdf_from_polygon, sdf_from_predicate, BitmapDiff, GenDebug, GenError,
+    Polarity, DEFAULT_MAX_DIMENSION,
+};
+pub use math::Distance;
+pub use path::{BuildReport, FillConvention, ShapeBuilder};
+pub use shape::{
+    Contour, Curve, Line, Quad, Segment, Shape, ShapeIssue,
+    SignedDistanceSegment, Winding,
+};
+pub use vector::{Affine2, Vector2};
 
-pub use rusttype::{Scale, VMetrics};
+pub use rusttype::{OutlineBuilder, Scale, VMetrics};
@@ -16,14 +16,49 @@ use crate::vector::Vector2;
 
 /// Distance from pixel to contour
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Distance {
     pub extended_dist: f32,
     pub real_dist: f32,
     pub orthogonality: f32,
+    /// `1.0` if the point is on the inner side of the segment for a
+    /// clockwise contour and `-1.0` otherwise.
+    ///
+    /// The side is undefined for a point on the segment or on its
+    /// extension, where the sign is `0.0`. [`Contour::distance`] resolves
+    /// such signs from the winding number of the contour.
+    ///
+    /// [`Contour::distance`]: crate::Contour::distance
+    #[cfg_attr(feature = "serde", serde(with = "serde_sign"))]
     pub sign: f32,
 }
 
+/// Serializes the NaN sign of [`Distance::MAX`] as a missing value since
+/// formats like JSON can't represent NaN.
+#[cfg(feature = "serde")]
+mod serde_sign {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        sign: &f32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let sign = if sign.is_nan() { None } else { Some(*sign) };
+        sign.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<f32, D::Error> {
+        Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::NAN))
+    }
+}
+
 impl Distance {
+    /// The largest distance, without a sign.
+    ///
+    /// The signed distances of it are `NaN`, so use [`Distance::FAR`]
+    /// where the distance can end up in a bitmap.
     pub const MAX: Self = Distance {
         extended_dist: f32::MAX,
         real_dist: f32::MAX,
@@ -31,6 +66,15 @@ impl Distance {
         sign: f32::NAN,
     };
 
+    /// The largest distance with a positive sign, returned when there is
+    /// nothing to measure the distance to, like for an empty contour.
+    ///
+    /// Unlike [`Distance::MAX`] its signed distances are finite.
+    pub const FAR: Self = Distance {
+        sign: 1.0,
+        ..Self::MAX
+    };
+
     #[inline]
     pub fn real_signed(self) -> f32 {
         // Maybe use a struct for sign to ensure it's not a zero.
@@ -44,11 +88,24 @@ impl Distance {
     }
 }
 
+impl Distance {
+    /// Relative difference under which two real distances are considered
+    /// equal and the [`Distance::orthogonality`] decides which one is
+    /// closer.
+    ///
+    /// The tolerance is multiplied by the larger of the two distances so
+    /// the comparison gives the same result no matter the scale the shape
+    /// was built with.
+    pub const EPSILON: f32 = 0.01;
+}
+
 impl PartialOrd for Distance {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         use std::cmp::Ordering;
         let diff = self.real_dist - other.real_dist;
-        match diff.abs().partial_cmp(&0.01) {
+        let tolerance =
+            Self::EPSILON * self.real_dist.abs().max(other.real_dist.abs());
+        match diff.abs().partial_cmp(&tolerance) {
             Some(Ordering::Less) => {
                 other.orthogonality.partial_cmp(&self.orthogonality)
             }
@@ -63,7 +120,31 @@ impl PartialOrd for Distance {
     }
 }
 
+/// Returns the sign of the side of the segment the point is on from the
+/// cross product of the direction and the vector to the point.
+///
+/// `f32::signum` turns a zero into `1.0` or `-1.0` depending on the sign
+/// bit of the zero, so an undefined side is kept as `0.0` instead, see
+/// [`Distance::sign`].
+#[inline]
+fn side(ortho: f32) -> f32 {
+    if ortho == 0.0 {
+        0.0
+    } else {
+        ortho.signum()
+    }
+}
+
 pub fn line_signed_distance(line: &Line, point: Vector2<f32>) -> Distance {
+    line_closest_point(line, point).0
+}
+
+/// Returns the [`Distance`] to the provided point together with the
+/// closest point on the line.
+pub(crate) fn line_closest_point(
+    line: &Line,
+    point: Vector2<f32>,
+) -> (Distance, Vector2<f32>) {
     let p0 = line.from;
     let p1 = line.to;
     let p = point;
@@ -94,18 +175,81 @@ pub fn line_signed_distance(line: &Line, point: Vector2<f32>) -> Distance {
     } else {
         p1_p0.normalize().cross(p_bezier.normalize())
     };
-    let sign = ortho.signum();
+    let sign = side(ortho);
     let orthogonality = ortho.abs();
 
-    Distance {
+    let distance = Distance {
         extended_dist,
         real_dist,
         orthogonality,
         sign,
-    }
+    };
+    (distance, bezier)
 }
 
 pub fn quad_signed_distance(quad: &Quad, point: Vector2<f32>) -> Distance {
+    quad_closest_point(quad, point).0
+}
+
+/// Returns the [`Distance`] to the provided point together with the
+/// closest point on the quadratic curve.
+pub(crate) fn quad_closest_point(
+    quad: &Quad,
+    point: Vector2<f32>,
+) -> (Distance, Vector2<f32>) {
+    let p0 = quad.from;
+    let p1 = quad.ctrl;
+    let p = point;
+
+    let v1 = p1 - p0;
+    let v2 = quad.to - 2.0 * p1 + p0;
+
+    let real_pos = quad_nearest_t(quad, point);
+    let closest_bezier = real_pos * real_pos * v2 + 2.0 * real_pos * v1 + p0;
+    let smallest_dist2 = (closest_bezier - p).magnitude2();
+
+    // Get the distance from current pixel "p" to bezier line.
+    let real_dist = smallest_dist2.sqrt();
+    // When the closest point is an endpoint, the curve is extended with
+    // its tangent at that endpoint, the same way the neighbouring segment
+    // continuing in the same direction would be.
+    let extended_dist = if real_pos == 0.0 || real_pos == 1.0 {
+        let tangent = quad_tangent(v1, v2, real_pos);
+        let endpoint_p = p - closest_bezier;
+        // Only points past the endpoint are projected on the extension.
+        let past = if real_pos == 0.0 { -1.0 } else { 1.0 };
+        if tangent.is_zero() || endpoint_p.dot(tangent) * past <= 0.0 {
+            real_dist
+        } else {
+            tangent.normalize().cross(endpoint_p).abs()
+        }
+    } else {
+        real_dist
+    };
+
+    // Invert the vector to get distance from bezier line to "p". TODO explain
+    let dir = quad_tangent(v1, v2, real_pos);
+    let p_bezier = p - closest_bezier;
+    let ortho: f32 = if p_bezier.is_zero() || dir.is_zero() {
+        0.0
+    } else {
+        dir.normalize().cross(p_bezier.normalize())
+    };
+    let sign = side(ortho);
+    let orthogonality = ortho.abs();
+
+    let distance = Distance {
+        extended_dist,
+        real_dist,
+        orthogonality,
+        sign,
+    };
+    (distance, closest_bezier)
+}
+
+/// Returns the parameter `t`, clamped to `[0, 1]`, of the point on the
+/// quadratic curve closest to the provided point.
+pub(crate) fn quad_nearest_t(quad: &Quad, point: Vector2<f32>) -> f32 {
     let p0 = quad.from;
     let p1 = quad.ctrl;
     let p2 = quad.to;
@@ -127,9 +271,7 @@ pub fn quad_signed_distance(quad: &Quad, point: Vector2<f32>) -> Distance {
     // Get roots:
     let roots = cubic_roots(a, b, c, d);
 
-    let mut extended_pos = 0.0;
     let mut real_pos = 0.0;
-    let mut closest_bezier = Vector2::new(f32::MAX, f32::MAX);
     let mut smallest_dist2 = f32::MAX; // Not square rooted
 
     // Compare all roots to find the closest "t" and smallest distance.
@@ -142,44 +284,209 @@ pub fn quad_signed_distance(quad: &Quad, point: Vector2<f32>) -> Distance {
         // Then compare the distances for each root.
         let dist2 = (bezier - p).magnitude2();
         if dist2 < smallest_dist2 {
-            extended_pos = *r;
             real_pos = t;
-            closest_bezier = bezier;
             smallest_dist2 = dist2;
         }
     }
 
-    // Get the distance from current pixel "p" to bezier line.
-    let extended_bezier =
-        extended_pos * extended_pos * v2 + 2.0 * extended_pos * v1 + p0;
-    let extended_dist = (extended_bezier - p).magnitude();
-    let real_dist = smallest_dist2.sqrt();
+    real_pos
+}
 
-    // Invert the vector to get distance from bezier line to "p". TODO explain
-    let dir = 2.0 * v2 * real_pos + 2.0 * v1;
+/// Returns the derivative of the quadratic bezier function at `t`, where
+/// `v1 = p1 - p0` and `v2 = p2 - 2 * p1 + p0`.
+#[inline]
+fn quad_tangent(v1: Vector2<f32>, v2: Vector2<f32>, t: f32) -> Vector2<f32> {
+    2.0 * v2 * t + 2.0 * v1
+}
+
+/// Returns the parameter `t`, clamped to `[0, 1]`, of the point on the
+/// cubic curve closest to the provided point.
+///
+/// The closest of evenly spaced samples is refined with Newton's method.
+pub(crate) fn curve_nearest_t(curve: &Curve, point: Vector2<f32>) -> f32 {
+    const SAMPLES: usize = 16;
+    let p0 = curve.from;
+    // Polynomial form: a * t^3 + b * t^2 + c * t + p0
+    let c = 3.0 * (curve.ctrl1 - p0);
+    let b = 3.0 * (curve.ctrl2 - 2.0 * curve.ctrl1 + p0);
+    let a = curve.to - 3.0 * curve.ctrl2 + 3.0 * curve.ctrl1 - p0;
+    let bezier = |t: f32| ((a * t + b) * t + c) * t + p0;
+
+    let mut best = 0.0;
+    let mut smallest_dist2 = f32::MAX;
+    for i in 0..=SAMPLES {
+        let t = i as f32 / SAMPLES as f32;
+        let dist2 = (bezier(t) - point).magnitude2();
+        if dist2 < smallest_dist2 {
+            best = t;
+            smallest_dist2 = dist2;
+        }
+    }
+
+    // Find the root of the derivative of the squared distance.
+    let mut t: f32 = best;
+    for _ in 0..8 {
+        let p_bezier = bezier(t) - point;
+        let d1 = (3.0 * a * t + 2.0 * b) * t + c;
+        let d2 = 6.0 * a * t + 2.0 * b;
+        let numerator = p_bezier.dot(d1);
+        let denominator = d1.dot(d1) + p_bezier.dot(d2);
+        if denominator == 0.0 {
+            break;
+        }
+        t = (t - numerator / denominator).clamp(0.0, 1.0);
+    }
+
+    if (bezier(t) - point).magnitude2() <= smallest_dist2 {
+        t
+    } else {
+        best
+    }
+}
+
+/// Returns the signed number of times the line crosses the ray going from
+/// the point in the positive x direction, `+1` for every crossing going
+/// down (with the y axis pointing down) and `-1` for every crossing going up.
+///
+/// The starting point is counted as below the ray when it lies on it and
+/// the ending point the same way, so a crossing through a shared endpoint
+/// of two segments is only counted once.
+pub(crate) fn line_ray_crossings(
+    from: Vector2<f32>,
+    to: Vector2<f32>,
+    point: Vector2<f32>,
+) -> i32 {
+    if (from.y <= point.y) == (to.y <= point.y) {
+        return 0;
+    }
+    let t = (point.y - from.y) / (to.y - from.y);
+    let x = from.x + t * (to.x - from.x);
+    match x > point.x {
+        true if to.y > from.y => 1,
+        true => -1,
+        false => 0,
+    }
+}
+
+/// Same as [`line_ray_crossings`] but for a quadratic curve.
+///
+/// The curve is split at its vertical extremum into parts going only up or
+/// only down, each crossing the ray at most once.
+pub(crate) fn quad_ray_crossings(quad: &Quad, point: Vector2<f32>) -> i32 {
+    let (p0, p1, p2) = (quad.from, quad.ctrl, quad.to);
+    // y(t) = a * t^2 + b * t + c
+    let a = p0.y - 2.0 * p1.y + p2.y;
+    let b = 2.0 * (p1.y - p0.y);
+    let c = p0.y - point.y;
+    let bezier = |t: f32| {
+        let u = 1.0 - t;
+        u * u * p0 + 2.0 * u * t * p1 + t * t * p2
+    };
+
+    let mut bounds = [0.0, 1.0, 1.0];
+    if a != 0.0 {
+        let extremum = -b / (2.0 * a);
+        if extremum > 0.0 && extremum < 1.0 {
+            bounds[1] = extremum;
+        }
+    }
+
+    let mut crossings = 0;
+    for part in bounds.windows(2).filter(|part| part[0] < part[1]) {
+        let (start, end) = (bezier(part[0]), bezier(part[1]));
+        if (start.y <= point.y) == (end.y <= point.y) {
+            continue;
+        }
+        // The root inside of the monotonic part.
+        let t = quadratic_roots(a, b, c)
+            .into_iter()
+            .flatten()
+            .map(|t| t.clamp(part[0], part[1]))
+            .min_by(|t1, t2| {
+                let y1 = (bezier(*t1).y - point.y).abs();
+                let y2 = (bezier(*t2).y - point.y).abs();
+                y1.partial_cmp(&y2).unwrap()
+            });
+        let x = match t {
+            Some(t) => bezier(t).x,
+            // Numerically lost root, fall back to the chord.
+            None => {
+                let t = (point.y - start.y) / (end.y - start.y);
+                start.x + t * (end.x - start.x)
+            }
+        };
+        if x > point.x {
+            crossings += if end.y > start.y { 1 } else { -1 };
+        }
+    }
+
+    crossings
+}
+
+pub fn curve_signed_distance(curve: &Curve, point: Vector2<f32>) -> Distance {
+    curve_closest_point(curve, point).0
+}
+
+/// Returns the [`Distance`] to the provided point together with the
+/// closest point on the cubic curve.
+pub(crate) fn curve_closest_point(
+    curve: &Curve,
+    point: Vector2<f32>,
+) -> (Distance, Vector2<f32>) {
+    let p0 = curve.from;
+    let p = point;
+    // Polynomial form: a * t^3 + b * t^2 + c * t + p0
+    let c = 3.0 * (curve.ctrl1 - p0);
+    let b = 3.0 * (curve.ctrl2 - 2.0 * curve.ctrl1 + p0);
+    let a = curve.to - 3.0 * curve.ctrl2 + 3.0 * curve.ctrl1 - p0;
+
+    let real_pos = curve_nearest_t(curve, point);
+    let closest_bezier = ((a * real_pos + b) * real_pos + c) * real_pos + p0;
+    let real_dist = (closest_bezier - p).magnitude();
+
+    // A control point lying on its end point gives no tangent there, so
+    // the direction towards the next distinct point is used instead.
+    let mut dir = (3.0 * a * real_pos + 2.0 * b) * real_pos + c;
+    if dir.is_zero() {
+        dir = if real_pos < 0.5 {
+            curve.ctrl2 - p0
+        } else {
+            curve.to - curve.ctrl1
+        };
+    }
+
+    // Same as for the quadratic curves, the curve is extended with its
+    // tangent past the end points.
     let p_bezier = p - closest_bezier;
+    let extended_dist = if real_pos == 0.0 || real_pos == 1.0 {
+        let past = if real_pos == 0.0 { -1.0 } else { 1.0 };
+        if dir.is_zero() || p_bezier.dot(dir) * past <= 0.0 {
+            real_dist
+        } else {
+            dir.normalize().cross(p_bezier).abs()
+        }
+    } else {
+        real_dist
+    };
+
     let ortho: f32 = if p_bezier.is_zero() || dir.is_zero() {
         0.0
     } else {
         dir.normalize().cross(p_bezier.normalize())
     };
-    let sign = ortho.signum();
+    let sign = side(ortho);
     let orthogonality = ortho.abs();
 
-    Distance {
+    let distance = Distance {
         extended_dist,
         real_dist,
         orthogonality,
         sign,
-    }
-}
-
-#[allow(unused)]
-pub fn curve_signed_distance(curve: &Curve, point: Vector2<f32>) -> Distance {
-    unimplemented!()
+    };
+    (distance, closest_bezier)
 }
 
-fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+pub(crate) fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
     let discriminant = b * b - 4.0 * a * c;
 
     if a == 0.0 {
@@ -192,12 +499,18 @@ fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
     if discriminant < 0.0 {
         [None, None]
     } else if discriminant > 0.0 {
+        // Adding numbers of the same sign avoids the cancellation between
+        // `b` and the square root when `a * c` is tiny compared to `b * b`.
+        // The second root is then found from `x1 * x2 = c / a`.
         let discriminant_sqrt = discriminant.sqrt();
-        let a2 = 1.0 / (2.0 * a);
-        // Root 1
-        let x1 = -(b + discriminant_sqrt) * a2;
-        // Root 2
-        let x2 = (discriminant_sqrt - b) * a2;
+        let q = -0.5 * (b + discriminant_sqrt.copysign(b));
+        let (larger, smaller) = (q / a, c / q);
+        // Root 1 is `-(b + sqrt) / 2a` and root 2 is `(sqrt - b) / 2a`.
+        let (x1, x2) = if b.is_sign_negative() {
+            (smaller, larger)
+        } else {
+            (larger, smaller)
+        };
 
         [Some(x1), Some(x2)]
     } else {
@@ -260,6 +573,7 @@ fn cubic_roots(a: f32, b: f32, c: f32, d: f32) -> [Option<f32>; 3] {
 ///
 /// Procedure explained at:
 /// https://web.archive.org/web/20121001232059/http://paulbourke.net/geometry/lineline2d/
+#[allow(unused)]
 pub fn line_line_intersection(
     line1: &Line,
     line2: &Line,
@@ -305,10 +619,57 @@ pub fn line_line_intersection(
     None
 }
 
+/// Returns the shared part of two lines lying on the same line, which is the
+/// coincident case not recorded by [`line_line_intersection`].
+///
+/// The returned points are ordered in the direction of the first line and
+/// are always endpoints of one of the lines. Lines touching at a single
+/// point don't share anything so `None` is returned.
+pub fn line_line_overlap(
+    line1: &Line,
+    line2: &Line,
+) -> Option<(Vector2<f32>, Vector2<f32>)> {
+    let p0 = line1.from;
+    let p1 = line1.to;
+    let p2 = line2.from;
+    let p3 = line2.to;
+
+    let p1_p0 = p1 - p0;
+    let length2 = p1_p0.dot(p1_p0);
+    if length2 == 0.0 {
+        return None;
+    }
+    // Both points of the second line have to be on the first line.
+    let tolerance = 1e-4 * length2;
+    if p1_p0.cross(p2 - p0).abs() > tolerance
+        || p1_p0.cross(p3 - p0).abs() > tolerance
+    {
+        return None;
+    }
+
+    // Positions of the second line points on the first line function.
+    let t2 = (p2 - p0).dot(p1_p0) / length2;
+    let t3 = (p3 - p0).dot(p1_p0) / length2;
+    let (start, end) = if t2 < t3 {
+        ((t2, p2), (t3, p3))
+    } else {
+        ((t3, p3), (t2, p2))
+    };
+
+    let start = if start.0 > 0.0 { start } else { (0.0, p0) };
+    let end = if end.0 < 1.0 { end } else { (1.0, p1) };
+    if end.0 - start.0 <= 0.0 {
+        return None;
+    }
+
+    Some((start.1, end.1))
+}
+
 // TODO exclain and improve and simplify
 /// Sources:
 /// https://www.particleincell.com/2013/cubic-line-intersection/
 /// https://stackoverflow.com/questions/50129580/program-to-find-line-segment-and-bezier-curve-intersection
+#[allow(unused)]
 pub fn quad_line_intersection(
     quad: &Quad,
     line: &Line,
@@ -319,6 +680,12 @@ pub fn quad_line_intersection(
     let l0 = line.from;
     let l1 = line.to;
 
+    // A zero-length line has no direction to intersect along and would
+    // divide by zero below.
+    if l0 == l1 {
+        return [None, None];
+    }
+
     let mut intersections = [None; 2];
     let a = (q0 + q2 - 2.0 * q1).cross(l1 - l0);
     let b = 2.0 * (q1 - q0).cross(l1 - l0);
@@ -343,9 +710,42 @@ pub fn quad_line_intersection(
 
     //println!("roots: {:?}", quad_roots);
 
+    // A line touching the curve gives the same point for both roots.
+    if let [Some(first), Some(second)] = intersections {
+        let mut points = vec![first, second];
+        dedup_points(&mut points, INTERSECTION_EPSILON);
+        if points.len() == 1 {
+            intersections[1] = None;
+        }
+    }
+
     intersections
 }
 
+/// Distance under which two intersection points are considered the same
+/// point.
+pub const INTERSECTION_EPSILON: f32 = 1e-4;
+
+/// Removes points which are closer than `eps` to an earlier point, so
+/// a single contact found twice, like a tangential one, is counted once.
+///
+/// The order of the kept points doesn't change.
+pub fn dedup_points(points: &mut Vec<Vector2<f32>>, eps: f32) {
+    let eps2 = eps * eps;
+    let mut kept = 0;
+    for i in 0..points.len() {
+        let point = points[i];
+        if points[..kept]
+            .iter()
+            .all(|&p| (p - point).magnitude2() > eps2)
+        {
+            points[kept] = point;
+            kept += 1;
+        }
+    }
+    points.truncate(kept);
+}
+
 #[allow(unused)]
 pub fn quad_quad_intersection(
     quad1: &Quad,
@@ -407,6 +807,25 @@ pub fn quadratic_fn(
     p0 + 2.0 * t * (p1 - p0) + t * t * (p2 - 2.0 * p1 + p0)
 }
 
+#[test]
+fn degenerate_line_quad_intersection_test() {
+    let quad = Quad {
+        from: Vector2::new(2.5, 2.0),
+        ctrl: Vector2::new(3.0, 8.0),
+        to: Vector2::new(10.0, 12.0),
+    };
+
+    // Both a point on the curve and a point away from it.
+    let on_curve = quadratic_fn(quad.from, quad.ctrl, quad.to, 0.5);
+    for point in [on_curve, Vector2::new(4.0, 5.0)] {
+        let line = Line {
+            from: point,
+            to: point,
+        };
+        assert_eq!(quad_line_intersection(&quad, &line), [None, None]);
+    }
+}
+
 #[test]
 fn line_quad_intersection_test() {
     // TODO maybe add more tests
@@ -455,6 +874,25 @@ fn line_quad_intersection_test() {
     assert!(quad_line_intersection(&quad, &line)[1].is_some());
 }
 
+#[test]
+fn dedup_points_test() {
+    let mut points = vec![
+        Vector2::new(1.0, 2.0),
+        Vector2::new(3.0, 4.0),
+        Vector2::new(1.0 + 1e-6, 2.0),
+        Vector2::new(1.0, 2.001),
+    ];
+    dedup_points(&mut points, INTERSECTION_EPSILON);
+    assert_eq!(
+        points,
+        [
+            Vector2::new(1.0, 2.0),
+            Vector2::new(3.0, 4.0),
+            Vector2::new(1.0, 2.001)
+        ]
+    );
+}
+
 #[test]
 fn line_intersection_test() {
     // Parallel lines:
@@ -549,6 +987,130 @@ fn line_intersection_test() {
     assert!(line_line_intersection(&line1, &line2).is_none());
 }
 
+#[test]
+fn quad_extended_distance_test() {
+    let quad = Quad::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(10.0, 0.0),
+        Vector2::new(10.0, 10.0),
+    );
+    // Continues the quad in the direction of its ending tangent.
+    let line = Line::new(Vector2::new(10.0, 10.0), Vector2::new(10.0, 20.0));
+
+    for i in 1..10 {
+        let point = Vector2::new(12.0, 10.0 + i as f32);
+        let quad_dist = quad_signed_distance(&quad, point);
+        let line_dist = line_signed_distance(&line, point);
+
+        assert!(
+            (quad_dist.extended_dist - line_dist.extended_dist).abs() < 1e-4
+        );
+        assert_eq!(quad_dist.sign, line_dist.sign);
+        // The real distance still goes to the endpoint.
+        assert!(quad_dist.real_dist > quad_dist.extended_dist);
+    }
+
+    // Points next to the body of the quad aren't extended.
+    let dist = quad_signed_distance(&quad, Vector2::new(5.0, 3.0));
+    assert_eq!(dist.extended_dist, dist.real_dist);
+}
+
+#[test]
+fn nearest_t_test() {
+    let quad = Quad::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(10.0, 20.0),
+        Vector2::new(20.0, 0.0),
+    );
+    assert!(quad.nearest_t(Vector2::new(-0.5, 0.2)) < 0.02);
+    assert!((quad.nearest_t(Vector2::new(10.0, 12.0)) - 0.5).abs() < 1e-4);
+    assert_eq!(quad.nearest_t(Vector2::new(25.0, -5.0)), 1.0);
+
+    let curve = Curve::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(0.0, 20.0),
+        Vector2::new(20.0, 20.0),
+        Vector2::new(20.0, 0.0),
+    );
+    assert!(curve.nearest_t(Vector2::new(-0.2, 0.5)) < 0.02);
+    assert!((curve.nearest_t(Vector2::new(10.0, 18.0)) - 0.5).abs() < 1e-4);
+    assert_eq!(curve.nearest_t(Vector2::new(25.0, -5.0)), 1.0);
+}
+
+#[test]
+fn ray_crossings_test() {
+    let point = Vector2::new(0.0, 5.0);
+    let down = line_ray_crossings(
+        Vector2::new(3.0, 0.0),
+        Vector2::new(3.0, 10.0),
+        point,
+    );
+    let up = line_ray_crossings(
+        Vector2::new(3.0, 10.0),
+        Vector2::new(3.0, 0.0),
+        point,
+    );
+    let behind = line_ray_crossings(
+        Vector2::new(-3.0, 0.0),
+        Vector2::new(-3.0, 10.0),
+        point,
+    );
+    assert_eq!((down, up, behind), (1, -1, 0));
+
+    // Crosses the ray twice in opposite directions.
+    let quad = Quad::new(
+        Vector2::new(2.0, 0.0),
+        Vector2::new(6.0, 20.0),
+        Vector2::new(10.0, 0.0),
+    );
+    assert_eq!(quad_ray_crossings(&quad, point), 0);
+    assert_eq!(quad_ray_crossings(&quad, Vector2::new(5.0, 5.0)), -1);
+    assert_eq!(quad_ray_crossings(&quad, Vector2::new(0.0, 12.0)), 0);
+}
+
+#[test]
+fn line_overlap_test() {
+    let line1 = Line::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0));
+
+    // Fully shared, opposite direction:
+    let line2 = Line::new(Vector2::new(10.0, 0.0), Vector2::new(0.0, 0.0));
+    assert_eq!(
+        line_line_overlap(&line1, &line2),
+        Some((Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0)))
+    );
+
+    // Partially shared:
+    let line2 = Line::new(Vector2::new(15.0, 0.0), Vector2::new(5.0, 0.0));
+    assert_eq!(
+        line_line_overlap(&line1, &line2),
+        Some((Vector2::new(5.0, 0.0), Vector2::new(10.0, 0.0)))
+    );
+
+    // Touching at a single point:
+    let line2 = Line::new(Vector2::new(10.0, 0.0), Vector2::new(20.0, 0.0));
+    assert!(line_line_overlap(&line1, &line2).is_none());
+
+    // Parallel:
+    let line2 = Line::new(Vector2::new(0.0, 1.0), Vector2::new(10.0, 1.0));
+    assert!(line_line_overlap(&line1, &line2).is_none());
+}
+
+#[test]
+fn ill_conditioned_quadratic_test() {
+    // Roots in double precision: -99999999.99 and 1.0000000099999997
+    let (a, b, c) = (1e-8_f32, 1.0, -1.0);
+    let mut roots = quadratic_roots(a, b, c).map(Option::unwrap);
+    roots.sort_by(|x1, x2| x1.partial_cmp(x2).unwrap());
+    assert!((roots[0] / -1e8 - 1.0).abs() < 1e-6);
+    assert!((roots[1] - 1.0).abs() < 1e-6);
+
+    // Cancellation happens for negative `b` as well.
+    let mut roots = quadratic_roots(1e-6, -3.0, 2.0).map(Option::unwrap);
+    roots.sort_by(|x1, x2| x1.partial_cmp(x2).unwrap());
+    assert!((roots[0] - 0.666_666_8).abs() < 1e-6);
+    assert!((roots[1] / 2_999_999.3 - 1.0).abs() < 1e-6);
+}
+
 #[test]
 fn cubic_root_test() {
     let a = 1.0;
@@ -623,3 +1185,57 @@ fn test_find_cubic_roots(
 
     (vec![x1, x2, x3], discriminant, q, r)
 }
+
+#[test]
+fn distance_epsilon_test() {
+    // Two edges meeting at a corner. The point is slightly closer to the
+    // vertical edge, but not by enough to ignore the orthogonality.
+    let compare = |scale: f32| {
+        let horizontal =
+            Line::new(Vector2::new(0.0, 0.0), Vector2::new(10.0 * scale, 0.0));
+        let vertical =
+            Line::new(Vector2::new(0.0, 10.0 * scale), Vector2::new(0.0, 0.0));
+        let point = Vector2::new(-1.0, 0.1) * scale;
+        let a = line_signed_distance(&horizontal, point);
+        let b = line_signed_distance(&vertical, point);
+        assert!(b.real_dist < a.real_dist);
+        a.partial_cmp(&b)
+    };
+
+    let expected = compare(1.0);
+    for scale in [0.01, 0.5, 4.0, 100.0, 1000.0] {
+        assert_eq!(compare(scale), expected);
+    }
+}
+
+#[test]
+fn far_distance_test() {
+    assert_eq!(Distance::FAR.real_signed(), f32::MAX);
+    assert!(Distance::FAR.pseudo_signed().is_finite());
+    assert!(Distance::MAX.real_signed().is_nan());
+
+    let empty = crate::shape::Contour::new(Vec::new(), crate::Winding(true));
+    let distance = empty.distance(Vector2::new(1.0, 2.0));
+    assert!(distance.real_signed().is_finite());
+    assert_eq!(distance, Distance::FAR);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn distance_serde_test() {
+    let distance = Distance {
+        extended_dist: 2.5,
+        real_dist: 3.0,
+        orthogonality: 0.75,
+        sign: -1.0,
+    };
+    let json = serde_json::to_string(&distance).unwrap();
+    assert_eq!(serde_json::from_str::<Distance>(&json).unwrap(), distance);
+
+    let json = serde_json::to_string(&Distance::MAX).unwrap();
+    let max: Distance = serde_json::from_str(&json).unwrap();
+    assert_eq!(max.extended_dist, f32::MAX);
+    assert_eq!(max.real_dist, f32::MAX);
+    assert_eq!(max.orthogonality, 0.0);
+    assert!(max.sign.is_nan());
+}
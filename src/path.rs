@@ -1,9 +1,24 @@
 use rusttype::{OutlineBuilder, Scale};
 
 use crate::shape::{Contour, Curve, Line, Quad, Segment, Shape, Winding};
-use crate::vector::Vector2;
+use crate::vector::{Affine2, Vector2};
 use crate::{BBox, Offset};
 
+/// Direction in which the filled contours of an outline source go.
+///
+/// The builder reverses contours of sources following the PostScript
+/// convention, so filled contours of a built [`Shape`] always go clockwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FillConvention {
+    /// Filled contours go clockwise, as in TrueType (`glyf`) outlines.
+    #[default]
+    TrueType,
+    /// Filled contours go counter-clockwise, as in PostScript (`CFF` and
+    /// `CFF2`) outlines. Contours are closed implicitly, even without a
+    /// final `close` instruction.
+    PostScript,
+}
+
 /// `PathBuilder` (aka. `ShapeBuilder`) builds a path from five opentype font instructions:
 /// - `move_to`
 /// - `line_to`
@@ -16,12 +31,17 @@ use crate::{BBox, Offset};
 pub struct PathBuilder {
     contours: Vec<Contour>,
     offset: Offset,
+    fill: FillConvention,
     //scale: NormScale,
 
+    // Contours from which zero-length segments were collapsed.
+    collapsed: Vec<bool>,
+
     // Temporary values
     shoelace: f32,
     last_point: Option<Vector2<f32>>,
     temp_segments: Vec<Segment>,
+    temp_collapsed: bool,
 }
 
 impl PathBuilder {
@@ -34,14 +54,28 @@ impl PathBuilder {
         Self {
             contours: Vec::new(),
             offset,
+            fill: FillConvention::default(),
+
+            collapsed: Vec::new(),
 
             shoelace: 0.0,
             last_point: None,
             temp_segments: Vec::new(),
+            temp_collapsed: false,
         }
     }
 
+    /// Sets the fill convention of the instructions, which is
+    /// [`FillConvention::TrueType`] by default.
+    pub fn set_fill_convention(&mut self, fill: FillConvention) {
+        self.fill = fill;
+    }
+
     pub fn open_at(&mut self, x: f32, y: f32) {
+        if self.fill == FillConvention::PostScript && self.last_point.is_some()
+        {
+            self.close();
+        }
         self.open_at_check();
 
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
@@ -53,11 +87,8 @@ impl PathBuilder {
 
         let from = self.last_point.unwrap();
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
-        let line = Line::new(from, to);
 
-        self.shoelace += line.shoelace();
-        self.temp_segments.push(Segment::Line(line));
-        self.last_point = Some(to);
+        self.push_segment(Segment::Line(Line::new(from, to)));
     }
 
     pub fn quad_to(&mut self, ctrl_x: f32, ctrl_y: f32, x: f32, y: f32) {
@@ -67,12 +98,8 @@ impl PathBuilder {
         let control =
             Vector2::new(ctrl_x + self.offset.x, ctrl_y + self.offset.y);
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
-        let quad = Quad::new(from, control, to);
 
-        self.shoelace += quad.shoelace();
-        self.temp_segments
-            .push(Segment::Quad(Quad::new(from, control, to)));
-        self.last_point = Some(to);
+        self.push_segment(Segment::Quad(Quad::new(from, control, to)));
     }
 
     pub fn curve_to(
@@ -92,24 +119,68 @@ impl PathBuilder {
         let ctrl2 =
             Vector2::new(ctrl2_x + self.offset.x, ctrl2_y + self.offset.y);
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
-        let curve = Curve::new(from, ctrl1, ctrl2, to);
 
-        self.shoelace += curve.shoelace();
-        self.temp_segments
-            .push(Segment::Curve(Curve::new(from, ctrl1, ctrl2, to)));
-        self.last_point = Some(to);
-        unimplemented!("Not implemented!!!")
+        self.push_segment(Segment::Curve(Curve::new(from, ctrl1, ctrl2, to)));
+    }
+
+    /// Adds the segment to the current contour, unless all of its points
+    /// are the same, so no zero-length segment reaches the distance code.
+    fn push_segment(&mut self, segment: Segment) {
+        if segment.is_degenerate() {
+            self.temp_collapsed = true;
+            return;
+        }
+
+        self.shoelace += match &segment {
+            Segment::Quad(quad) => quad.shoelace(),
+            _ => segment.start().cross(segment.end()),
+        };
+        self.last_point = Some(segment.end());
+        self.temp_segments.push(segment);
     }
 
     pub fn close(&mut self) {
         self.close_check();
 
-        // TODO test if windings are right
-        let winding = Winding(self.shoelace < 0.0);
+        if self.fill == FillConvention::PostScript {
+            // PostScript contours end with an implicit line to the start.
+            let start = self.temp_segments.first().map(Segment::start);
+            let last = self.last_point.unwrap();
+            if let Some(start) = start.filter(|&start| start != last) {
+                let line = Line::new(last, start);
+                self.shoelace += line.shoelace();
+                self.temp_segments.push(Segment::Line(line));
+            }
+        }
+
+        // With the y axis pointing down a positive shoelace sum means
+        // the contour goes clockwise.
+        let clockwise = self.shoelace > 0.0;
+        let reversed = self.fill == FillConvention::PostScript;
+        let winding = Winding(clockwise != reversed);
         //println!("winding: {:?}", winding);
         let segments = self.temp_segments.drain(..).collect::<Vec<_>>();
 
-        self.contours.push(Contour::new(segments, winding));
+        let mut contour = Contour::new(segments, winding);
+        if reversed {
+            contour.reverse();
+        }
+        self.contours.push(contour);
+        self.collapsed.push(self.temp_collapsed);
+        self.temp_collapsed = false;
+        self.shoelace = 0.0;
+        self.last_point = None;
+    }
+
+    /// Ends the current contour without connecting it back to its start,
+    /// making it an open centerline, see [`Contour::new_open`].
+    pub fn end_open(&mut self) {
+        self.close_check();
+
+        let segments = self.temp_segments.drain(..).collect::<Vec<_>>();
+        self.contours.push(Contour::new_open(segments));
+        self.collapsed.push(self.temp_collapsed);
+        self.temp_collapsed = false;
         self.shoelace = 0.0;
         self.last_point = None;
     }
@@ -152,10 +223,6 @@ impl PathBuilder {
 
     #[inline]
     fn close_check(&self) {
-        assert!(
-            !self.temp_segments.is_empty(),
-            "PathBuilder Error: There are no contours to close or there are zero segments!"
-        );
         assert!(
             self.last_point.is_some(),
             "PathBuilder Error: The last contour has already been closed!"
@@ -163,37 +230,70 @@ impl PathBuilder {
     }
 
     pub fn build_shape(self) -> Shape {
-        assert!(
-            !self.contours.is_empty(),
-            "PathBuilder Error: There are no contours."
-        );
+        self.build_shape_with_report().0
+    }
+
+    /// Builds the shape after removing zero-length segments and contours
+    /// which are left without any segments. The shape is empty if all of
+    /// the contours were removed.
+    ///
+    /// Returns the shape together with a [`BuildReport`] describing
+    /// the changes.
+    pub fn build_shape_with_report(mut self) -> (Shape, BuildReport) {
+        if self.fill == FillConvention::PostScript && self.last_point.is_some()
+        {
+            self.close();
+        }
         assert!(
             self.last_point.is_none(),
             "PathBuilder Error: The last contour is still open."
         );
 
-        Shape::new(self.contours)
+        let mut report = BuildReport::default();
+        let mut collapsed = self.collapsed.iter();
+        self.contours.retain_mut(|contour| {
+            let count = contour.segments.len();
+            let collapsed = *collapsed.next().unwrap();
+            contour.segments.retain(|segment| !segment.is_degenerate());
+            if contour.segments.is_empty() {
+                report.removed_contours += 1;
+                false
+            } else {
+                if collapsed || contour.segments.len() != count {
+                    report.repaired_contours += 1;
+                }
+                true
+            }
+        });
+
+        // A path whose contours were all removed gives an empty shape, the
+        // same as a glyph without an outline.
+        (Shape::new(self.contours), report)
     }
 
-    pub fn build_shape_scaled(mut self, scale: Scale) -> Shape {
+    pub fn build_shape_scaled_with_report(
+        mut self,
+        scale: Scale,
+    ) -> (Shape, BuildReport) {
         for contour in self.contours.iter_mut() {
             for segment in contour.segments.iter_mut() {
                 match segment {
                     Segment::Line(l) => l.rescale(scale),
                     Segment::Quad(q) => q.rescale(scale),
                     Segment::Curve(c) => c.rescale(scale),
+                    Segment::Custom(_) => {
+                        unreachable!("Custom segments aren't built by a path")
+                    }
                 }
             }
         }
 
-        self.build_shape()
+        self.build_shape_with_report()
     }
 }
 
 impl OutlineBuilder for PathBuilder {
     fn move_to(&mut self, x: f32, y: f32) {
-        println!("moving to: {} {}", x, y);
-
         self.open_at(x, y);
     }
 
@@ -232,6 +332,80 @@ impl OutlineBuilder for PathBuilder {
     }
 }
 
+/// Forwards outline instructions given in font units to a [`PathBuilder`]
+/// after flipping the y axis and applying a transformation.
+pub(crate) struct FontUnitsBuilder<'b> {
+    path: &'b mut PathBuilder,
+    transform: Affine2,
+}
+
+impl<'b> FontUnitsBuilder<'b> {
+    /// Scales the instructions by `scale` and moves them by `shift`.
+    pub(crate) fn new(
+        path: &'b mut PathBuilder,
+        scale: Vector2<f32>,
+        shift: Vector2<f32>,
+    ) -> Self {
+        let transform = Affine2::scale(scale.x, scale.y)
+            .then(Affine2::translation(shift.x, shift.y));
+        Self::with_transform(path, transform)
+    }
+
+    /// Applies the `transform` to the instructions, with the y axis
+    /// already pointing down.
+    pub(crate) fn with_transform(
+        path: &'b mut PathBuilder,
+        transform: Affine2,
+    ) -> Self {
+        Self { path, transform }
+    }
+
+    #[inline]
+    fn transform(&self, x: f32, y: f32) -> (f32, f32) {
+        let point = self.transform.transform(Vector2::new(x, -y));
+        (point.x, point.y)
+    }
+}
+
+impl OutlineBuilder for FontUnitsBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform(x, y);
+        self.path.open_at(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform(x, y);
+        self.path.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.transform(x1, y1);
+        let (x, y) = self.transform(x, y);
+        self.path.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.transform(x1, y1);
+        let (x2, y2) = self.transform(x2, y2);
+        let (x, y) = self.transform(x, y);
+        self.path.curve_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+/// Describes the cleanup done while building a shape.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Number of contours from which zero-length segments were removed.
+    pub repaired_contours: usize,
+    /// Number of contours removed because they had no non-zero-length
+    /// segments.
+    pub removed_contours: usize,
+}
+
 pub struct ShapeBuilder {
     path: PathBuilder,
     bbox: BBox,
@@ -248,7 +422,10 @@ impl ShapeBuilder {
         let builder = PathBuilder::new(offset);
         let bbox = BBox::new(
             Vector2::ZERO_I32,
-            Vector2::new(width as i32, height as i32),
+            Vector2::new(
+                width.min(i32::MAX as u32) as i32,
+                height.min(i32::MAX as u32) as i32,
+            ),
         );
         Self {
             path: builder,
@@ -257,6 +434,12 @@ impl ShapeBuilder {
         }
     }
 
+    /// Sets the fill convention of the instructions, which is
+    /// [`FillConvention::TrueType`] by default.
+    pub fn set_fill_convention(&mut self, fill: FillConvention) {
+        self.path.set_fill_convention(fill);
+    }
+
     pub fn open_at(&mut self, x: f32, y: f32) {
         self.path.open_at(x, y);
     }
@@ -285,14 +468,140 @@ impl ShapeBuilder {
         self.path.close();
     }
 
-    pub fn build(mut self) -> (Shape, BBox) {
-        if let Some(scale) = self.scale {
+    /// Ends the current contour as an open centerline, see
+    /// [`PathBuilder::end_open`].
+    pub fn end_open(&mut self) {
+        self.path.end_open();
+    }
+
+    pub fn build(self) -> (Shape, BBox) {
+        let (shape, bbox, _) = self.build_with_report();
+        (shape, bbox)
+    }
+
+    /// Same as [`ShapeBuilder::build`] but also returns a [`BuildReport`]
+    /// listing the contours which were repaired or removed.
+    pub fn build_with_report(mut self) -> (Shape, BBox, BuildReport) {
+        let (shape, report) = if let Some(scale) = self.scale {
             self.bbox.scale(scale);
-            (self.path.build_shape_scaled(scale), self.bbox)
+            self.path.build_shape_scaled_with_report(scale)
         } else {
-            (self.path.build_shape(), self.bbox)
-        }
+            self.path.build_shape_with_report()
+        };
+
+        (shape, self.bbox, report)
     }
 }
 
 // TODO maybe add tests for each module
+
+#[test]
+fn fill_convention_test() {
+    // Filled square going counter-clockwise with the last line omitted.
+    let build = |fill| {
+        let mut builder = ShapeBuilder::new(16, 16, None, Offset::uniform(0.0));
+        builder.set_fill_convention(fill);
+        builder.open_at(4.0, 4.0);
+        builder.line_to(4.0, 12.0);
+        builder.line_to(12.0, 12.0);
+        builder.line_to(12.0, 4.0);
+        if fill == FillConvention::TrueType {
+            builder.line_to(4.0, 4.0);
+        }
+        builder.close();
+        builder.build().0
+    };
+
+    let true_type = build(FillConvention::TrueType);
+    assert!(!true_type.contours[0].winding().is_cw());
+    let post_script = build(FillConvention::PostScript);
+    assert_eq!(post_script.contours[0].segments.len(), 4);
+    assert!(post_script.contours[0].winding().is_cw());
+    assert!(post_script.validate().is_empty());
+    assert!(
+        post_script.contours[0]
+            .distance(Vector2::new(8.0, 8.0))
+            .sign
+            > 0.0
+    );
+}
+
+#[test]
+fn duplicate_points_test() {
+    let mut builder = ShapeBuilder::new(16, 16, None, Offset::uniform(0.0));
+    builder.open_at(2.0, 2.0);
+    builder.line_to(2.0, 2.0);
+    builder.line_to(10.0, 2.0);
+    builder.line_to(10.0, 2.0);
+    builder.quad_to(10.0, 2.0, 10.0, 2.0);
+    builder.close();
+
+    let (shape, _, report) = builder.build_with_report();
+    assert_eq!(shape.contours[0].segments.len(), 1);
+    assert_eq!(report.repaired_contours, 1);
+    match &shape.contours[0].segments[0] {
+        Segment::Line(l) => {
+            assert_eq!(l.from, Vector2::new(2.0, 2.0));
+            assert_eq!(l.to, Vector2::new(10.0, 2.0));
+        }
+        _ => panic!("Expected a line"),
+    }
+}
+
+#[test]
+fn degenerate_contour_test() {
+    let mut builder = ShapeBuilder::new(16, 16, None, Offset::uniform(0.0));
+    // Empty contour
+    builder.open_at(2.0, 2.0);
+    builder.close();
+    // Zero-length contour
+    builder.open_at(5.0, 5.0);
+    builder.line_to(5.0, 5.0);
+    builder.close();
+    // Triangle with a duplicated point
+    builder.open_at(2.0, 2.0);
+    builder.line_to(14.0, 2.0);
+    builder.line_to(14.0, 2.0);
+    builder.line_to(8.0, 14.0);
+    builder.line_to(2.0, 2.0);
+    builder.close();
+
+    let (shape, bbox, report) = builder.build_with_report();
+    assert_eq!(report.removed_contours, 2);
+    assert_eq!(report.repaired_contours, 1);
+    assert_eq!(shape.contours.len(), 1);
+    assert_eq!(shape.contours[0].segments.len(), 3);
+
+    let outline = crate::GlyphOutline::from_shape(
+        shape,
+        bbox,
+        Scale::uniform(1.0),
+        Offset::uniform(0.0),
+    );
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert!(sdf.distances().iter().all(|d| d.is_finite()));
+}
+
+#[test]
+fn all_degenerate_contours_test() {
+    let mut builder = ShapeBuilder::new(16, 16, None, Offset::uniform(0.0));
+    builder.open_at(5.0, 5.0);
+    builder.line_to(5.0, 5.0);
+    builder.close();
+    builder.open_at(8.0, 8.0);
+    builder.quad_to(8.0, 8.0, 8.0, 8.0);
+    builder.close();
+
+    let (shape, bbox, report) = builder.build_with_report();
+    assert_eq!(report.removed_contours, 2);
+    assert!(shape.contours.is_empty() && shape.is_empty());
+
+    let outline = crate::GlyphOutline::from_shape(
+        shape,
+        bbox,
+        Scale::uniform(1.0),
+        Offset::uniform(0.0),
+    );
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert!(sdf.distances().iter().all(|&d| d == -2.0));
+}
@@ -2,7 +2,12 @@ use std::slice::Iter;
 
 use rusttype::Scale;
 
-use crate::{font::BBox, math::Distance, vector::Vector2};
+use crate::{
+    coloring::{color_contour, color_shape, EdgeColor},
+    font::BBox,
+    math::{line_line_intersection, Distance},
+    vector::{Affine2, Vector2},
+};
 
 #[derive(Debug)]
 pub struct Shape {
@@ -15,121 +20,1669 @@ impl Shape {
         Self { contours }
     }
 
-    /// Returns a bounding box which is created paying attention to
-    /// line and curve points instead of their bodies.
-    /// TODO: maybe not needed
-    pub fn bbox(&self) -> BBox {
-        let mut x_iter = Vec::new();
-        let mut y_iter = Vec::new();
-        for contour in self.contours.iter() {
-            for seg in contour.iter() {
-                match seg {
-                    Segment::Line(l) => {
-                        x_iter.push(l.from.x);
-                        x_iter.push(l.to.x);
-
-                        y_iter.push(l.from.y);
-                        y_iter.push(l.to.y);
+    /// Moves all contours of the `other` shape to the end of this shape.
+    ///
+    /// The shapes are expected to be in the same coordinate system, so
+    /// overlapping contours fill the same area.
+    pub fn append(&mut self, other: Shape) {
+        self.contours.extend(other.contours);
+    }
+
+    /// Creates a single shape with the contours of all provided shapes, in
+    /// the provided order, see [`Shape::append`].
+    pub fn merge(shapes: Vec<Shape>) -> Self {
+        let mut merged = Shape::new(Vec::new());
+        for shape in shapes {
+            merged.append(shape);
+        }
+        merged
+    }
+
+    /// Returns the contours together with their role, which is `true` for
+    /// additive contours filling the area they surround and `false` for
+    /// subtractive contours cutting it out.
+    ///
+    /// The role is derived from the [`Winding`] of the contour.
+    pub fn contours_with_role(&self) -> impl Iterator<Item = (&Contour, bool)> {
+        self.contours
+            .iter()
+            .map(|contour| (contour, contour.winding().is_cw()))
+    }
+
+    /// Returns the point on the outline closest to the provided point
+    /// together with the signed distance to it.
+    ///
+    /// The distance is positive inside of the shape, the same as in the
+    /// generated fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shape has no segments.
+    pub fn closest_point(&self, point: Vector2<f32>) -> (Vector2<f32>, f32) {
+        let (distance, closest) = self
+            .contours
+            .iter()
+            .flat_map(|contour| contour.iter())
+            .map(|segment| segment.closest_point(point))
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .expect("Shape Error: The shape has no segments!");
+        (closest, self.sign_at(point) * distance.real_dist)
+    }
+
+    /// Returns the segment closest to the provided point across all
+    /// contours as the index of the contour, the index of the segment in
+    /// the contour, the parameter `t` of the closest point on the segment
+    /// and the [`Distance`] to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shape has no segments.
+    pub fn closest_feature(
+        &self,
+        point: Vector2<f32>,
+    ) -> (usize, usize, f32, Distance) {
+        let (contour, segment, distance) = self
+            .contours
+            .iter()
+            .enumerate()
+            .filter_map(|(c, contour)| {
+                contour
+                    .closest_segment(point)
+                    .map(|(s, distance)| (c, s, distance))
+            })
+            .reduce(|accum, item| if accum.2 < item.2 { accum } else { item })
+            .expect("Shape Error: The shape has no segments!");
+        let t = self.contours[contour].segments[segment].nearest_t(point);
+        (contour, segment, t, distance)
+    }
+
+    /// Returns the sum of the winding numbers of all contours around the
+    /// point.
+    ///
+    /// Points with a non-zero winding number are inside of the shape,
+    /// regardless of the order of the contours.
+    pub fn winding_number(&self, point: Vector2<f32>) -> i32 {
+        self.contours
+            .iter()
+            .map(|contour| contour.winding_number(point))
+            .sum()
+    }
+
+    /// Assigns an [`EdgeColor`] to every segment of every contour, read
+    /// back with [`Contour::segment_colors`].
+    ///
+    /// Unlike coloring every contour with [`Contour::color_edges`], the
+    /// colors are compatible across contours, so segments of different
+    /// contours don't share a color where the contours come closest.
+    pub fn color_edges(&mut self, angle_threshold: f32) {
+        let colors = color_shape(self, angle_threshold);
+        for (contour, colors) in self.contours.iter_mut().zip(colors) {
+            contour.colors = colors;
+        }
+    }
+
+    /// Returns how many other contours contain each contour, in the order
+    /// of the contours.
+    ///
+    /// A contour is contained only if it lies entirely inside of the other
+    /// contour without crossing or touching it, so overlapping contours,
+    /// common in variable fonts, don't contain each other. A fill inside
+    /// of a hole inside of a fill has the depths `0`, `1` and `2`, so
+    /// even-odd filling can be derived from the parity of the depth. Empty
+    /// and open contours have the depth `0`.
+    pub fn nesting_depths(&self) -> Vec<u32> {
+        self.contours
+            .iter()
+            .enumerate()
+            .map(|(i, contour)| {
+                self.contours
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, other)| j != i && other.contains(contour))
+                    .count() as u32
+            })
+            .collect()
+    }
+
+    /// Returns the sign of the distance to the outline at the point, `1.0`
+    /// inside and `-1.0` outside of the shape.
+    #[inline]
+    pub(crate) fn sign_at(&self, point: Vector2<f32>) -> f32 {
+        if self.winding_number(point) != 0 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    /// Checks the shape for problems which break the generation and returns
+    /// all of them without modifying the shape.
+    ///
+    /// Returns an empty list if the shape is valid.
+    pub fn validate(&self) -> Vec<ShapeIssue> {
+        let mut issues = Vec::new();
+        for (c, contour) in self.contours.iter().enumerate() {
+            let (first, last) =
+                match (contour.segments.first(), contour.segments.last()) {
+                    (Some(first), Some(last)) => (first, last),
+                    _ => {
+                        issues.push(ShapeIssue::EmptyContour { contour: c });
+                        continue;
                     }
-                    Segment::Quad(q) => {
-                        x_iter.push(q.from.x);
-                        x_iter.push(q.ctrl.x);
-                        x_iter.push(q.to.x);
-
-                        y_iter.push(q.from.y);
-                        y_iter.push(q.ctrl.y);
-                        y_iter.push(q.to.y);
+                };
+
+            for (s, segment) in contour.iter().enumerate() {
+                let mut finite = true;
+                segment.for_each_point(|p| {
+                    finite &= p.x.is_finite() && p.y.is_finite();
+                });
+                if !finite {
+                    issues.push(ShapeIssue::NonFiniteCoordinate {
+                        contour: c,
+                        segment: s,
+                    });
+                } else if segment.is_degenerate() {
+                    issues.push(ShapeIssue::DegenerateSegment {
+                        contour: c,
+                        segment: s,
+                    });
+                }
+            }
+
+            if contour.is_open() {
+                continue;
+            }
+            // Custom segments may not end exactly where they started.
+            if (last.end() - first.start()).magnitude() > 1e-4 {
+                issues.push(ShapeIssue::OpenContour { contour: c });
+            }
+            if contour.is_flat() {
+                issues.push(ShapeIssue::ZeroArea { contour: c });
+            }
+        }
+
+        issues
+    }
+
+    /// Merges contours of the same winding which touch along a shared line
+    /// edge into a single contour.
+    ///
+    /// The shared edge is removed from both contours so it doesn't leave
+    /// a seam in the generated field. Contours which overlap are not
+    /// affected.
+    pub fn union(&mut self) {
+        while let Some((first, second)) = self.split_shared_edge() {
+            let contour = self.contours.remove(second.0);
+            self.contours[first.0].merge(first.1, contour, second.1);
+        }
+    }
+
+    /// Finds two contours of the same winding touching along line edges
+    /// going in opposite directions and splits the edges so the shared part
+    /// becomes a separate segment in both contours.
+    ///
+    /// Returns the (contour, segment) indices of the shared segments, the
+    /// first contour always coming before the second one.
+    fn split_shared_edge(
+        &mut self,
+    ) -> Option<((usize, usize), (usize, usize))> {
+        for i in 0..self.contours.len() {
+            for j in i + 1..self.contours.len() {
+                if self.contours[i].winding != self.contours[j].winding {
+                    continue;
+                }
+                for si in 0..self.contours[i].segments.len() {
+                    for sj in 0..self.contours[j].segments.len() {
+                        let (a, b) = match (
+                            &self.contours[i].segments[si],
+                            &self.contours[j].segments[sj],
+                        ) {
+                            (Segment::Line(a), Segment::Line(b)) => (a, b),
+                            _ => continue,
+                        };
+                        if (a.to - a.from).dot(b.to - b.from) >= 0.0 {
+                            continue;
+                        }
+                        if let Some((start, end)) =
+                            crate::math::line_line_overlap(a, b)
+                        {
+                            let si =
+                                self.contours[i].split_line(si, start, end);
+                            let sj =
+                                self.contours[j].split_line(sj, end, start);
+                            return Some(((i, si), (j, sj)));
+                        }
                     }
-                    Segment::Curve(c) => {
-                        x_iter.push(c.from.x);
-                        x_iter.push(c.ctrl1.x);
-                        x_iter.push(c.ctrl2.x);
-                        x_iter.push(c.to.x);
-
-                        y_iter.push(c.from.y);
-                        y_iter.push(c.ctrl1.y);
-                        y_iter.push(c.ctrl2.y);
-                        y_iter.push(c.to.y);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Mirrors the shape along the chosen axes inside of an area of the
+    /// provided `size` starting at the origin.
+    ///
+    /// Contours are reversed when mirrored along a single axis to keep
+    /// their direction, so filled areas stay filled.
+    pub(crate) fn mirror(&mut self, x: bool, y: bool, size: Vector2<f32>) {
+        let mirror = Affine2 {
+            xx: if x { -1.0 } else { 1.0 },
+            yy: if y { -1.0 } else { 1.0 },
+            tx: if x { size.x } else { 0.0 },
+            ty: if y { size.y } else { 0.0 },
+            ..Affine2::IDENTITY
+        };
+        for contour in self.contours.iter_mut() {
+            for segment in contour.segments.iter_mut() {
+                *segment = segment.transformed(&mirror);
+            }
+            if x != y {
+                contour.reverse();
+            }
+        }
+    }
+
+    /// Returns the windings the contours have after
+    /// [`Shape::normalize_windings`], without changing the shape.
+    pub fn normalized_windings(&self) -> Vec<Winding> {
+        self.contours
+            .iter()
+            .zip(self.winding_flips())
+            .map(|(contour, flip)| Winding(contour.winding().is_cw() != flip))
+            .collect()
+    }
+
+    /// Makes the shape as a whole go clockwise, so additive contours have
+    /// a clockwise [`Winding`], and returns the windings of the contours.
+    ///
+    /// All contours are reversed if the shape goes counter-clockwise, like
+    /// outlines following the PostScript convention which weren't built
+    /// with [`FillConvention::PostScript`](crate::FillConvention).
+    ///
+    /// Some broken fonts wind all contours the same way, so a hole can't
+    /// be told apart from a fill by its direction and fills the shape. If
+    /// all closed contours share a direction while some of them lie
+    /// entirely inside of others, the contours are oriented by the even-odd
+    /// rule instead, see [`Shape::nesting_depths`]. Contours which only
+    /// overlap each other, like the parts of many variable font glyphs,
+    /// stay fills.
+    pub fn normalize_windings(&mut self) -> Vec<Winding> {
+        let flips = self.winding_flips();
+        for (contour, flip) in self.contours.iter_mut().zip(flips) {
+            if flip {
+                contour.reverse();
+                contour.winding = Winding(contour.winding.is_ccw());
+            }
+        }
+        self.contours.iter().map(Contour::winding).collect()
+    }
+
+    /// Splits the contours into the fills and the holes of the shape, for
+    /// renderers filling them in separate passes.
+    ///
+    /// Contours are oriented the same way as by [`Shape::normalize_windings`],
+    /// so fills go clockwise and holes counter-clockwise. Open contours
+    /// enclose nothing and are left out.
+    pub fn split_fills_holes(&self) -> (Vec<Contour>, Vec<Contour>) {
+        self.contours
+            .iter()
+            .zip(self.winding_flips())
+            .filter(|(contour, _)| !contour.open)
+            .map(|(contour, flip)| {
+                let mut copy = Contour {
+                    segments: contour.iter().map(Segment::copied).collect(),
+                    winding: contour.winding,
+                    open: false,
+                    colors: contour.colors.clone(),
+                };
+                if flip {
+                    copy.reverse();
+                    copy.winding = Winding(copy.winding.is_ccw());
+                }
+                copy
+            })
+            .partition(|contour| contour.winding().is_cw())
+    }
+
+    /// Returns which contours [`Shape::normalize_windings`] reverses.
+    fn winding_flips(&self) -> Vec<bool> {
+        let closed = self
+            .contours
+            .iter()
+            .filter(|contour| !contour.open && !contour.segments.is_empty())
+            .map(|contour| contour.winding().is_cw())
+            .collect::<Vec<_>>();
+        let uniform =
+            closed.len() > 1 && closed.iter().all(|&cw| cw == closed[0]);
+        if uniform {
+            let depths = self.nesting_depths();
+            if depths.iter().any(|depth| depth % 2 == 1) {
+                // Fills on even depths go clockwise, holes on odd depths
+                // counter-clockwise.
+                return self
+                    .contours
+                    .iter()
+                    .zip(depths)
+                    .map(|(contour, depth)| {
+                        !contour.open
+                            && contour.winding().is_cw() != (depth % 2 == 0)
+                    })
+                    .collect();
+            }
+        }
+
+        let flip = self.signed_area() < 0.0;
+        vec![flip; self.contours.len()]
+    }
+
+    /// Returns the number of segments in all contours.
+    pub fn segment_count(&self) -> usize {
+        self.contours
+            .iter()
+            .map(|contour| contour.segments.len())
+            .sum()
+    }
+
+    /// Returns the sum of the signed areas of all contours, see
+    /// [`Contour::signed_area`].
+    pub fn signed_area(&self) -> f32 {
+        self.contours.iter().map(Contour::signed_area).sum()
+    }
+
+    /// Checks if the shape has nothing to draw, either because it has no
+    /// segments or because all of its contours are closed and flat, with
+    /// every point on a single line.
+    ///
+    /// A self-intersecting contour can have a zero signed area with lobes
+    /// winding in opposite directions, so the area alone doesn't tell if
+    /// anything is inside.
+    ///
+    /// Generating a field of an empty shape skips the distance calculations
+    /// and returns a field which is entirely outside.
+    pub fn is_empty(&self) -> bool {
+        self.contours
+            .iter()
+            .all(|contour| !contour.is_open() && contour.is_flat())
+    }
+
+    /// Returns points placed evenly along every contour, roughly `spacing`
+    /// apart when measured along the outline.
+    ///
+    /// The spacing is adjusted for every contour so the points fit its
+    /// length exactly. Every contour gets at least one point, placed at its
+    /// start. Curves are measured by splitting them into short lines.
+    ///
+    /// Panics if `spacing` isn't positive.
+    pub fn sample_outline(&self, spacing: f32) -> Vec<Vector2<f32>> {
+        const STEPS: usize = 16;
+        assert!(
+            spacing > 0.0,
+            "Shape Error: The sampling spacing must be positive."
+        );
+
+        let mut points = Vec::new();
+        for contour in &self.contours {
+            // Flatten the contour into a polyline.
+            let mut polyline = Vec::new();
+            for segment in contour.iter() {
+                let steps = match segment {
+                    Segment::Line(_) => 1,
+                    _ => STEPS,
+                };
+                if polyline.is_empty() {
+                    polyline.push(segment.start());
+                }
+                polyline.extend(
+                    (1..=steps)
+                        .map(|i| segment.point_at(i as f32 / steps as f32)),
+                );
+            }
+            if polyline.is_empty() {
+                continue;
+            }
+            let length: f32 = polyline
+                .windows(2)
+                .map(|line| (line[1] - line[0]).magnitude())
+                .sum();
+
+            let count = (length / spacing).round().max(1.0) as usize;
+            let step = length / count as f32;
+            let mut placed = 0;
+            let mut travelled = 0.0;
+            for line in polyline.windows(2) {
+                let line_length = (line[1] - line[0]).magnitude();
+                // Distance of the next point from the start of the contour.
+                let mut next = placed as f32 * step;
+                while placed < count && next <= travelled + line_length {
+                    let t = if line_length > 0.0 {
+                        (next - travelled) / line_length
+                    } else {
+                        0.0
+                    };
+                    points.push(line[0] + t * (line[1] - line[0]));
+                    placed += 1;
+                    next = placed as f32 * step;
+                }
+                travelled += line_length;
+            }
+        }
+
+        points
+    }
+
+    /// Returns a copy of the shape built at the scale `from` as if it was
+    /// built at the scale `to`, so a glyph parsed once can serve many sizes.
+    ///
+    /// All points are scaled linearly around `(0, 0)`, which scales the
+    /// offset around a glyph outline too. Edge colors are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a scale isn't positive.
+    pub fn rescale(&self, from: Scale, to: Scale) -> Shape {
+        assert!(
+            from.x > 0.0 && from.y > 0.0 && to.x > 0.0 && to.y > 0.0,
+            "Shape Error: The scales must be positive."
+        );
+        let factor = Scale {
+            x: to.x / from.x,
+            y: to.y / from.y,
+        };
+
+        let contours = self
+            .contours
+            .iter()
+            .map(|contour| Contour {
+                segments: contour.iter().map(|s| s.scaled(factor)).collect(),
+                winding: contour.winding,
+                open: contour.open,
+                colors: contour.colors.clone(),
+            })
+            .collect();
+
+        Shape::new(contours)
+    }
+
+    /// Returns a copy of the shape with every curve replaced by lines which
+    /// stay within `tolerance` pixels of the curve.
+    ///
+    /// The distances of a shape made only of lines are computed without
+    /// solving any polynomials, which makes it a robust fallback when the
+    /// curves cause trouble.
+    pub fn flatten(&self, tolerance: f32) -> Shape {
+        assert!(
+            tolerance > 0.0,
+            "Shape Error: The flattening tolerance must be positive."
+        );
+
+        let contours = self
+            .contours
+            .iter()
+            .map(|contour| {
+                let points = contour.polyline(tolerance);
+                let segments = points
+                    .windows(2)
+                    .filter(|line| line[0] != line[1])
+                    .map(|line| Segment::Line(Line::new(line[0], line[1])))
+                    .collect();
+                Contour {
+                    segments,
+                    winding: contour.winding,
+                    open: contour.open,
+                    colors: Vec::new(),
+                }
+            })
+            .collect();
+
+        Shape::new(contours)
+    }
+
+    /// Returns a copy of the shape sheared horizontally to stand upright,
+    /// undoing the slant of an italic font with the italic `angle` in
+    /// degrees from [`Font::italic_angle`](crate::Font::italic_angle).
+    ///
+    /// Points move horizontally in proportion to their y coordinate, so
+    /// the line `y = 0` stays in place and the shape can move sideways. Use
+    /// [`Shape::bbox`] to create a [`GlyphOutline`](crate::GlyphOutline)
+    /// from it. Edge colors are kept.
+    pub fn deskew(&self, angle: f32) -> Shape {
+        let transform = Affine2::skew_x(angle.to_radians());
+        let contours = self
+            .contours
+            .iter()
+            .map(|contour| Contour {
+                segments: contour
+                    .iter()
+                    .map(|segment| segment.transformed(&transform))
+                    .collect(),
+                winding: contour.winding,
+                open: contour.open,
+                colors: contour.colors.clone(),
+            })
+            .collect();
+
+        Shape::new(contours)
+    }
+
+    /// Returns a copy of the shape with fewer segments, where runs of
+    /// nearly collinear segments are merged into single lines.
+    ///
+    /// Curves which stay within `tolerance` pixels of their chord count as
+    /// lines. A run of lines is merged as long as every point dropped from
+    /// the outline stays within `tolerance` pixels of the merged line, so
+    /// the outline moves by at most `tolerance`. Other curves are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tolerance` is negative.
+    pub fn simplify(&self, tolerance: f32) -> Shape {
+        assert!(
+            tolerance >= 0.0,
+            "Shape Error: The simplification tolerance can't be negative."
+        );
+        /// Either points joined by lines, merged for as long as the
+        /// dropped points stay close enough, or a curve which is kept.
+        enum Piece {
+            Run(Vec<Vector2<f32>>),
+            Kept(Segment),
+        }
+        let fits = |run: &[Vector2<f32>]| match run {
+            [from, inner @ .., to] => {
+                from != to
+                    && inner
+                        .iter()
+                        .all(|p| distance_to_line(*p, *from, *to) <= tolerance)
+            }
+            _ => true,
+        };
+
+        let contours = self
+            .contours
+            .iter()
+            .map(|contour| {
+                let mut pieces = Vec::new();
+                for segment in contour.iter() {
+                    if segment.chord_deviation() > tolerance {
+                        pieces.push(Piece::Kept(segment.copied()));
+                        continue;
+                    }
+                    if let Some(Piece::Run(run)) = pieces.last_mut() {
+                        run.push(segment.end());
+                        if fits(run) {
+                            continue;
+                        }
+                        run.pop();
+                    }
+                    pieces
+                        .push(Piece::Run(vec![segment.start(), segment.end()]));
+                }
+
+                // The run ending the contour can continue into the first.
+                if !contour.open && pieces.len() > 1 {
+                    if let (Some(Piece::Run(last)), Some(Piece::Run(first))) =
+                        (pieces.last(), pieces.first())
+                    {
+                        let mut joined = last.clone();
+                        joined.extend_from_slice(&first[1..]);
+                        if fits(&joined) {
+                            pieces.pop();
+                            pieces[0] = Piece::Run(joined);
+                        }
+                    }
+                }
+
+                let segments = pieces
+                    .into_iter()
+                    .map(|piece| match piece {
+                        Piece::Run(run) => {
+                            Segment::Line(Line::new(run[0], run[run.len() - 1]))
+                        }
+                        Piece::Kept(segment) => segment,
+                    })
+                    .collect();
+                Contour {
+                    segments,
+                    winding: contour.winding,
+                    open: contour.open,
+                    colors: Vec::new(),
+                }
+            })
+            .collect();
+
+        Shape::new(contours)
+    }
+
+    /// Returns a copy of the shape with every sharp corner replaced by a
+    /// quadratic curve starting and ending `radius` pixels from the corner.
+    ///
+    /// The curve follows the directions of both segments where it meets
+    /// them, so the outline has no sharp corners left. Segments shorter than
+    /// two radii lose at most half of their length on each side.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radius` isn't positive.
+    pub fn round_corners(&self, radius: f32) -> Shape {
+        // Joints whose directions differ by less are already smooth.
+        const SMOOTH_ANGLE: f32 = 0.01;
+        assert!(
+            radius > 0.0,
+            "Shape Error: The corner radius must be positive."
+        );
+
+        let contours = self
+            .contours
+            .iter()
+            .map(|contour| {
+                let segments = &contour.segments;
+                let count = segments.len();
+                // Parameters where the rounded segments start and end.
+                let mut starts = vec![0.0; count];
+                let mut ends = vec![1.0; count];
+                let mut fillets = vec![None; count];
+
+                let joints = if contour.open {
+                    count.saturating_sub(1)
+                } else {
+                    count
+                };
+                for i in 0..joints {
+                    let (a, b) = (&segments[i], &segments[(i + 1) % count]);
+                    let turn =
+                        a.direction_at(1.0).angle_between(b.direction_at(0.0));
+                    if turn < SMOOTH_ANGLE {
+                        continue;
                     }
+                    let corner = a.end();
+                    let end = a.parameter_at_distance(corner, radius, false);
+                    let start = b.parameter_at_distance(corner, radius, true);
+                    let (from, to) = (a.point_at(end), b.point_at(start));
+                    // The control point lies where the directions at both
+                    // ends of the curve meet.
+                    let (dir_a, dir_b) =
+                        (a.direction_at(end), b.direction_at(start));
+                    let denominator = dir_a.cross(dir_b);
+                    let along = (to - from).cross(dir_b) / denominator;
+                    let ctrl = if denominator != 0.0 && along > 0.0 {
+                        from + along * dir_a
+                    } else {
+                        corner
+                    };
+                    ends[i] = end;
+                    starts[(i + 1) % count] = start;
+                    fillets[i] = Some((from, ctrl, to));
                 }
+
+                let mut rounded = Vec::with_capacity(count * 2);
+                for (i, segment) in segments.iter().enumerate() {
+                    let part = segment.part(starts[i], ends[i]);
+                    if !part.is_degenerate() {
+                        rounded.push(part);
+                    }
+                    if let Some((from, ctrl, to)) = fillets[i] {
+                        rounded.push(Segment::Quad(Quad::new(from, ctrl, to)));
+                    }
+                }
+
+                Contour {
+                    segments: rounded,
+                    winding: contour.winding,
+                    open: contour.open,
+                    colors: Vec::new(),
+                }
+            })
+            .collect();
+
+        Shape::new(contours)
+    }
+
+    /// Returns a bounding box tightly surrounding the bodies of all
+    /// segments.
+    ///
+    /// The box is rounded outwards to whole pixels and, same as every
+    /// [`BBox`], has the y coordinate increasing downwards.
+    pub fn bbox(&self) -> BBox {
+        let (min, max) = self.bounds();
+
+        BBox {
+            tl: Vector2::new(min.x.floor() as i32, min.y.floor() as i32),
+            br: Vector2::new(max.x.ceil() as i32, max.y.ceil() as i32),
+        }
+    }
+
+    /// Returns the smallest and the largest coordinates of the shape
+    /// using the tight bounds of every segment.
+    pub(crate) fn bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        self.contours
+            .iter()
+            .flat_map(|contour| contour.iter())
+            .map(|segment| segment.bounds())
+            .reduce(union_bounds)
+            .expect("No segments? Impossible!")
+    }
+}
+
+/// Returns bounds surrounding both of the provided bounds.
+fn union_bounds(
+    (min1, max1): (Vector2<f32>, Vector2<f32>),
+    (min2, max2): (Vector2<f32>, Vector2<f32>),
+) -> (Vector2<f32>, Vector2<f32>) {
+    (
+        Vector2::new(min1.x.min(min2.x), min1.y.min(min2.y)),
+        Vector2::new(max1.x.max(max2.x), max1.y.max(max2.y)),
+    )
+}
+
+/// A problem found by [`Shape::validate`].
+///
+/// Contours and segments are referenced by their indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeIssue {
+    /// The contour has no segments.
+    EmptyContour { contour: usize },
+    /// The last segment doesn't end where the first one starts.
+    OpenContour { contour: usize },
+    /// All points of the segment are at the same position.
+    DegenerateSegment { contour: usize, segment: usize },
+    /// A point of the segment is `NaN` or infinite.
+    NonFiniteCoordinate { contour: usize, segment: usize },
+    /// All points of the contour lie on a single line.
+    ZeroArea { contour: usize },
+}
+
+#[derive(Debug)]
+pub struct Contour {
+    pub segments: Vec<Segment>,
+    winding: Winding,
+    open: bool,
+    /// Colors assigned by [`Contour::color_edges`], one for every segment,
+    /// or empty if the contour isn't colored.
+    colors: Vec<EdgeColor>,
+}
+
+impl Contour {
+    pub fn new(segments: Vec<Segment>, winding: Winding) -> Self {
+        Self {
+            segments,
+            winding,
+            open: false,
+            colors: Vec::new(),
+        }
+    }
+
+    /// Creates an open contour, a centerline which doesn't surround any
+    /// area.
+    ///
+    /// Open contours are meant to be stroked. They never count as inside of
+    /// the shape, so every pixel gets the distance to the centerline outside
+    /// of the shape and [`Bitmap::stroke`](crate::Bitmap::stroke) turns the
+    /// field into the stroked line.
+    pub fn new_open(segments: Vec<Segment>) -> Self {
+        Self {
+            segments,
+            winding: Winding(true),
+            open: true,
+            colors: Vec::new(),
+        }
+    }
+
+    /// Checks if the contour is an open centerline, see
+    /// [`Contour::new_open`].
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Assigns an [`EdgeColor`] to every segment, so segments meeting at
+    /// a corner sharper than `angle_threshold` radians get different
+    /// colors.
+    ///
+    /// The colors are kept with the segments when the contour is reversed,
+    /// but are dropped when [`Shape::union`] merges it with another one.
+    pub fn color_edges(&mut self, angle_threshold: f32) {
+        self.colors = color_contour(self, angle_threshold);
+    }
+
+    /// Returns the colors assigned by [`Contour::color_edges`] in the order
+    /// of the segments, or an empty slice if the contour isn't colored.
+    ///
+    /// The colors go out of sync if the public segments are changed
+    /// afterwards, so the contour has to be colored again.
+    #[inline]
+    pub fn segment_colors(&self) -> &[EdgeColor] {
+        &self.colors
+    }
+
+    /// Returns the [`Distance`] to the provided point, which is
+    /// [`Distance::FAR`] if the contour has no segments.
+    ///
+    /// If the point lies on the closest segment or on its extension, the
+    /// side of the segment is undefined, so the sign comes from the winding
+    /// number instead: `1.0` if the contour winds around the point and
+    /// `-1.0` otherwise.
+    pub fn distance(&self, point: Vector2<f32>) -> Distance {
+        let distance = self
+            .segments
+            .iter()
+            .map(|segment| segment.distance(point))
+            .reduce(|accum, item| {
+                // To learn more about the comparison go to `SignedDistance::partial_cmp`
+                if accum < item {
+                    accum
+                } else {
+                    item
+                }
+            })
+            .unwrap_or(Distance::FAR);
+        self.resolve_sign(point, distance)
+    }
+
+    /// Checks if the `other` contour lies entirely inside of this one
+    /// without crossing or touching its outline.
+    ///
+    /// Curves of both contours are compared as lines following them
+    /// closely.
+    fn contains(&self, other: &Contour) -> bool {
+        const TOLERANCE: f32 = 0.05;
+        if self.open
+            || other.open
+            || self.segments.is_empty()
+            || other.segments.is_empty()
+        {
+            return false;
+        }
+        let (min, max) = self.bounds();
+        let (other_min, other_max) = other.bounds();
+        if other_min.x < min.x
+            || other_min.y < min.y
+            || other_max.x > max.x
+            || other_max.y > max.y
+        {
+            return false;
+        }
+
+        let outer = self.polyline(TOLERANCE);
+        let inner = other.polyline(TOLERANCE);
+        let crosses = |a: &[Vector2<f32>]| {
+            let a = Line::new(a[0], a[1]);
+            outer.windows(2).any(|b| {
+                line_line_intersection(&a, &Line::new(b[0], b[1])).is_some()
+            })
+        };
+        inner.iter().all(|&point| self.winding_number(point) != 0)
+            && !inner.windows(2).any(crosses)
+    }
+
+    /// Returns the points of lines which stay within `tolerance` pixels of
+    /// the segments, starting with the start of the first segment.
+    fn polyline(&self, tolerance: f32) -> Vec<Vector2<f32>> {
+        let mut points = Vec::new();
+        for segment in self.iter() {
+            if points.is_empty() {
+                points.push(segment.start());
+            }
+            segment.flatten_into(tolerance, &mut points);
+        }
+        points
+    }
+
+    /// Replaces the undefined sign of a distance to a point on a segment
+    /// with the sign given by the winding number.
+    fn resolve_sign(
+        &self,
+        point: Vector2<f32>,
+        distance: Distance,
+    ) -> Distance {
+        if distance.sign != 0.0 {
+            return distance;
+        }
+        let inside = self.winding_number(point) != 0;
+        Distance {
+            sign: if inside { 1.0 } else { -1.0 },
+            ..distance
+        }
+    }
+
+    /// Returns the smallest and the largest coordinates of the contour
+    /// using the tight bounds of every segment.
+    ///
+    /// A contour without segments has inverted infinite bounds, which
+    /// contain no point.
+    pub(crate) fn bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        self.segments.iter().map(Segment::bounds).fold(
+            (
+                Vector2::new(f32::INFINITY, f32::INFINITY),
+                Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            ),
+            union_bounds,
+        )
+    }
+
+    /// Returns the index of the segment closest to the provided point
+    /// together with the [`Distance`] to it, or [`None`] if the contour
+    /// has no segments.
+    ///
+    /// Segments are compared the same way as in [`Contour::distance`].
+    pub fn closest_segment(
+        &self,
+        point: Vector2<f32>,
+    ) -> Option<(usize, Distance)> {
+        self.segments
+            .iter()
+            .map(|segment| segment.distance(point))
+            .enumerate()
+            .reduce(|accum, item| if accum.1 < item.1 { accum } else { item })
+            .map(|(index, distance)| {
+                (index, self.resolve_sign(point, distance))
+            })
+    }
+
+    /// Returns the area surrounded by the contour, positive for clockwise
+    /// and negative for counter-clockwise contours.
+    ///
+    /// Open contours don't surround any area.
+    pub fn signed_area(&self) -> f32 {
+        if self.open {
+            return 0.0;
+        }
+        self.segments.iter().map(Segment::area).sum()
+    }
+
+    /// Returns how many times the contour winds around the point, positive
+    /// for clockwise and negative for counter-clockwise contours.
+    ///
+    /// Open contours don't wind around any point.
+    pub fn winding_number(&self, point: Vector2<f32>) -> i32 {
+        if self.open {
+            return 0;
+        }
+        self.segments
+            .iter()
+            .map(|segment| segment.ray_crossings(point))
+            .sum()
+    }
+
+    /// Splits the line at `index` so that the part from `start` to `end`
+    /// becomes its own segment and returns the index of that segment.
+    ///
+    /// Both points have to lie on the line, `start` being the closer one to
+    /// the line starting point.
+    fn split_line(
+        &mut self,
+        index: usize,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+    ) -> usize {
+        let (from, to) = match &self.segments[index] {
+            Segment::Line(l) => (l.from, l.to),
+            _ => panic!("Contour Error: Only lines can be split!"),
+        };
+        let mut pieces = Vec::new();
+        if from != start {
+            pieces.push(Segment::Line(Line::new(from, start)));
+        }
+        let shared = index + pieces.len();
+        pieces.push(Segment::Line(Line::new(start, end)));
+        if end != to {
+            pieces.push(Segment::Line(Line::new(end, to)));
+        }
+        self.segments.splice(index..=index, pieces);
+
+        shared
+    }
+
+    /// Joins the `other` contour into this one through a segment shared by
+    /// both contours in opposite directions, removing the shared segment.
+    fn merge(&mut self, index: usize, mut other: Contour, other_index: usize) {
+        // The other contour is rotated to start right after the shared
+        // segment which ends exactly where the removed one started.
+        other.segments.rotate_left(other_index);
+        other.segments.remove(0);
+        self.segments.splice(index..=index, other.segments);
+        self.remove_backtracks();
+        self.colors.clear();
+    }
+
+    /// Removes pairs of neighbouring lines which go back and forth over
+    /// the same edge, left over after merging contours sharing multiple
+    /// consecutive edges.
+    fn remove_backtracks(&mut self) {
+        let mut i = 0;
+        while self.segments.len() > 2 && i < self.segments.len() {
+            let next = (i + 1) % self.segments.len();
+            let backtrack = match (&self.segments[i], &self.segments[next]) {
+                (Segment::Line(a), Segment::Line(b)) => {
+                    a.from == b.to && a.to == b.from
+                }
+                _ => false,
+            };
+            if backtrack {
+                // Remove the higher index first so the lower one stays valid.
+                self.segments.remove(i.max(next));
+                self.segments.remove(i.min(next));
+                i = i.saturating_sub(1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Checks if all points of the contour lie on a single line, so the
+    /// contour doesn't surround any area.
+    fn is_flat(&self) -> bool {
+        let mut points = Vec::new();
+        for segment in self.iter() {
+            segment.for_each_point(|p| points.push(p));
+        }
+        let origin = match points.first() {
+            Some(&origin) => origin,
+            None => return true,
+        };
+        let direction = match points.iter().find(|&&p| p != origin) {
+            Some(&p) => (p - origin).normalize(),
+            None => return true,
+        };
+
+        points
+            .iter()
+            .all(|&p| direction.cross(p - origin).abs() <= f32::EPSILON * 16.0)
+    }
+
+    /// Reverses the direction of the contour.
+    pub(crate) fn reverse(&mut self) {
+        self.segments.reverse();
+        self.segments.iter_mut().for_each(Segment::reverse);
+        self.colors.reverse();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, Segment> {
+        self.segments.iter()
+    }
+
+    #[inline]
+    pub fn winding(&self) -> Winding {
+        self.winding
+    }
+}
+
+/// A user defined segment, like a circle arc, which can be placed into a
+/// [`Contour`] with [`Segment::Custom`].
+///
+/// The segment is parametrized by `t` in the interval `[0, 1]` and has to
+/// follow the same conventions as the built-in segments: the
+/// [`Distance::sign`] is positive on the right side of the segment
+/// direction (with the y axis pointing down), which is the inside of
+/// clockwise contours.
+///
+/// Segments have to be [`Send`] and [`Sync`] so that shapes and outlines
+/// can be generated on multiple threads.
+pub trait SignedDistanceSegment: std::fmt::Debug + Send + Sync {
+    /// Returns the [`Distance`] to the provided point.
+    fn distance(&self, point: Vector2<f32>) -> Distance;
+
+    /// Returns a bounding box surrounding the whole segment.
+    fn bbox(&self) -> BBox;
+
+    /// Returns the point on the segment at the parameter `t`.
+    fn point_at(&self, t: f32) -> Vector2<f32>;
+
+    /// Returns the direction (derivative) of the segment at the
+    /// parameter `t`.
+    fn direction_at(&self, t: f32) -> Vector2<f32>;
+
+    /// Returns a copy of the segment with all its points mapped through
+    /// the `transform`, used when the shape is scaled, mirrored or skewed.
+    fn transformed(
+        &self,
+        transform: &Affine2,
+    ) -> Box<dyn SignedDistanceSegment>;
+
+    /// Returns a copy of the segment going the opposite way, so the point
+    /// at `t` becomes the point at `1 - t`.
+    fn reversed(&self) -> Box<dyn SignedDistanceSegment>;
+
+    /// Returns the part of the segment between the parameters `t0` and
+    /// `t1`, reparametrized to `[0, 1]`.
+    fn part(&self, t0: f32, t1: f32) -> Box<dyn SignedDistanceSegment>;
+
+    /// Returns the point on the segment closest to the provided point.
+    ///
+    /// The default implementation uses
+    /// [`SignedDistanceSegment::nearest_t`].
+    fn closest_point(&self, point: Vector2<f32>) -> Vector2<f32> {
+        self.point_at(self.nearest_t(point))
+    }
+
+    /// Returns the parameter `t` of the point on the segment closest to
+    /// the provided point.
+    ///
+    /// The default implementation samples the segment with
+    /// [`SignedDistanceSegment::point_at`] and refines the parameter
+    /// around the closest sample.
+    fn nearest_t(&self, point: Vector2<f32>) -> f32 {
+        const SAMPLES: usize = 64;
+        let dist2 = |t: f32| (self.point_at(t) - point).magnitude2();
+
+        let step = 1.0 / SAMPLES as f32;
+        let mut best = 0.0;
+        for i in 1..=SAMPLES {
+            let t = i as f32 * step;
+            if dist2(t) < dist2(best) {
+                best = t;
+            }
+        }
+        // Ternary search between the neighbouring samples.
+        let (mut low, mut high) =
+            ((best - step).max(0.0), (best + step).min(1.0));
+        for _ in 0..32 {
+            let third = (high - low) / 3.0;
+            if dist2(low + third) < dist2(high - third) {
+                high -= third;
+            } else {
+                low += third;
+            }
+        }
+        (low + high) * 0.5
+    }
+}
+
+/// Splits the control points of a Bézier curve at the parameter `t` with
+/// de Casteljau's algorithm into the control points of both parts.
+fn split<const N: usize>(
+    mut points: [Vector2<f32>; N],
+    t: f32,
+) -> ([Vector2<f32>; N], [Vector2<f32>; N]) {
+    let (mut before, mut after) = (points, points);
+    for level in 0..N {
+        before[level] = points[0];
+        after[N - 1 - level] = points[N - 1 - level];
+        for i in 0..N - 1 - level {
+            points[i] = points[i] + t * (points[i + 1] - points[i]);
+        }
+    }
+    (before, after)
+}
+
+#[derive(Debug)]
+pub enum Segment {
+    Line(Line),
+    Quad(Quad),
+    Curve(Curve),
+    Custom(Box<dyn SignedDistanceSegment>),
+}
+
+impl Segment {
+    pub(crate) fn distance(&self, point: Vector2<f32>) -> Distance {
+        match self {
+            Segment::Line(l) => l.calculate_distance(point),
+            Segment::Quad(q) => q.calculate_distance(point),
+            Segment::Curve(c) => c.calculate_distance(point),
+            Segment::Custom(c) => c.distance(point),
+        }
+    }
+
+    /// Returns the [`Distance`] to the provided point together with the
+    /// closest point on the segment.
+    pub(crate) fn closest_point(
+        &self,
+        point: Vector2<f32>,
+    ) -> (Distance, Vector2<f32>) {
+        match self {
+            Segment::Line(l) => crate::math::line_closest_point(l, point),
+            Segment::Quad(q) => crate::math::quad_closest_point(q, point),
+            Segment::Curve(c) => crate::math::curve_closest_point(c, point),
+            Segment::Custom(c) => (c.distance(point), c.closest_point(point)),
+        }
+    }
+
+    /// Returns the signed area between the segment and the origin, which
+    /// sums up to the area of a closed contour.
+    ///
+    /// Cubic curves and custom segments are approximated by lines.
+    fn area(&self) -> f32 {
+        const SAMPLES: usize = 32;
+        match self {
+            Segment::Line(l) => 0.5 * l.from.cross(l.to),
+            Segment::Quad(q) => 0.5 * q.shoelace(),
+            Segment::Curve(_) | Segment::Custom(_) => (0..SAMPLES)
+                .map(|i| {
+                    let from = self.point_at(i as f32 / SAMPLES as f32);
+                    let to = self.point_at((i + 1) as f32 / SAMPLES as f32);
+                    0.5 * from.cross(to)
+                })
+                .sum(),
+        }
+    }
+
+    /// Pushes the points of lines approximating the segment within
+    /// `tolerance`, without the starting point, to `points`.
+    fn flatten_into(&self, tolerance: f32, points: &mut Vec<Vector2<f32>>) {
+        // The distance between a curve and its chords is bounded by an
+        // eighth of the second derivative times the squared parameter step.
+        let pieces = match self {
+            Segment::Line(l) => {
+                points.push(l.to);
+                return;
+            }
+            Segment::Quad(q) => {
+                let second = (q.from - 2.0 * q.ctrl + q.to).magnitude();
+                (second / (4.0 * tolerance)).sqrt()
+            }
+            Segment::Curve(c) => {
+                let second = (c.from - 2.0 * c.ctrl1 + c.ctrl2)
+                    .magnitude()
+                    .max((c.ctrl1 - 2.0 * c.ctrl2 + c.to).magnitude());
+                (3.0 * second / (4.0 * tolerance)).sqrt()
+            }
+            Segment::Custom(_) => {
+                // Nothing is known about custom segments, so the pieces are
+                // split until their middle is close enough to the chord.
+                const START: usize = 4;
+                for i in 0..START {
+                    self.subdivide(
+                        i as f32 / START as f32,
+                        (i + 1) as f32 / START as f32,
+                        tolerance,
+                        12,
+                        points,
+                    );
+                }
+                return;
+            }
+        };
+        let pieces = (pieces.ceil() as usize).max(1);
+        points.extend(
+            (1..=pieces).map(|i| self.point_at(i as f32 / pieces as f32)),
+        );
+    }
+
+    /// Pushes the end of the part of the segment between the parameters
+    /// `t0` and `t1`, splitting it in half until the middle of each part is
+    /// within `tolerance` of the line between its ends or the `depth` runs
+    /// out.
+    fn subdivide(
+        &self,
+        t0: f32,
+        t1: f32,
+        tolerance: f32,
+        depth: u32,
+        points: &mut Vec<Vector2<f32>>,
+    ) {
+        let (from, to) = (self.point_at(t0), self.point_at(t1));
+        let middle_t = 0.5 * (t0 + t1);
+        let middle = self.point_at(middle_t);
+        let deviation = Line::new(from, to).calculate_distance(middle);
+        if depth == 0 || deviation.real_dist <= tolerance {
+            points.push(to);
+        } else {
+            self.subdivide(t0, middle_t, tolerance, depth - 1, points);
+            self.subdivide(middle_t, t1, tolerance, depth - 1, points);
+        }
+    }
+
+    /// Returns the part of the segment between the parameters `t0` and
+    /// `t1` as a new segment of the same kind.
+    fn part(&self, t0: f32, t1: f32) -> Segment {
+        // Control points of the part of a curve between `t0` and `t1`,
+        // cutting off the end first and then the start of what's left.
+        fn cut<const N: usize>(
+            points: [Vector2<f32>; N],
+            t0: f32,
+            t1: f32,
+        ) -> [Vector2<f32>; N] {
+            let (before, _) = split(points, t1);
+            if t1 <= 0.0 {
+                return [before[0]; N];
+            }
+            split(before, t0 / t1).1
+        }
+
+        match self {
+            Segment::Line(l) => Segment::Line(Line::new(
+                self.point_at(t0),
+                if t1 == 1.0 { l.to } else { self.point_at(t1) },
+            )),
+            Segment::Quad(q) => {
+                let [from, ctrl, to] = cut([q.from, q.ctrl, q.to], t0, t1);
+                Segment::Quad(Quad::new(from, ctrl, to))
+            }
+            Segment::Curve(c) => {
+                let [from, ctrl1, ctrl2, to] =
+                    cut([c.from, c.ctrl1, c.ctrl2, c.to], t0, t1);
+                Segment::Curve(Curve::new(from, ctrl1, ctrl2, to))
+            }
+            Segment::Custom(c) => Segment::Custom(c.part(t0, t1)),
+        }
+    }
+
+    /// Returns the parameter of the point on the segment `distance` away
+    /// from the `corner`, which is the starting point if `from_start` is
+    /// `true` and the ending point otherwise.
+    ///
+    /// Only the half of the segment closer to the corner is searched.
+    fn parameter_at_distance(
+        &self,
+        corner: Vector2<f32>,
+        distance: f32,
+        from_start: bool,
+    ) -> f32 {
+        let (mut near, mut far) =
+            if from_start { (0.0, 0.5) } else { (1.0, 0.5) };
+        if (self.point_at(far) - corner).magnitude() <= distance {
+            return far;
+        }
+        for _ in 0..24 {
+            let middle = 0.5 * (near + far);
+            if (self.point_at(middle) - corner).magnitude() < distance {
+                near = middle;
+            } else {
+                far = middle;
+            }
+        }
+        0.5 * (near + far)
+    }
+
+    /// Returns the signed number of times the segment crosses the ray going
+    /// from the point in the positive x direction.
+    ///
+    /// Cubic curves and custom segments are approximated by lines.
+    pub(crate) fn ray_crossings(&self, point: Vector2<f32>) -> i32 {
+        const SAMPLES: usize = 32;
+        match self {
+            Segment::Line(l) => {
+                crate::math::line_ray_crossings(l.from, l.to, point)
+            }
+            Segment::Quad(q) => crate::math::quad_ray_crossings(q, point),
+            Segment::Curve(_) | Segment::Custom(_) => (0..SAMPLES)
+                .map(|i| {
+                    let from = self.point_at(i as f32 / SAMPLES as f32);
+                    let to = self.point_at((i + 1) as f32 / SAMPLES as f32);
+                    crate::math::line_ray_crossings(from, to, point)
+                })
+                .sum(),
+        }
+    }
+
+    /// Returns the parameter `t`, in `[0, 1]`, of the point on the segment
+    /// closest to the provided point.
+    pub fn nearest_t(&self, point: Vector2<f32>) -> f32 {
+        match self {
+            Segment::Line(l) => l.nearest_t(point),
+            Segment::Quad(q) => q.nearest_t(point),
+            Segment::Curve(c) => c.nearest_t(point),
+            Segment::Custom(c) => c.nearest_t(point),
+        }
+    }
+
+    /// Returns the point on the segment at the parameter `t`.
+    pub fn point_at(&self, t: f32) -> Vector2<f32> {
+        let u = 1.0 - t;
+        match self {
+            Segment::Line(l) => u * l.from + t * l.to,
+            Segment::Quad(q) => {
+                u * u * q.from + 2.0 * u * t * q.ctrl + t * t * q.to
+            }
+            Segment::Curve(c) => {
+                u * u * u * c.from
+                    + 3.0 * u * u * t * c.ctrl1
+                    + 3.0 * u * t * t * c.ctrl2
+                    + t * t * t * c.to
+            }
+            Segment::Custom(c) => c.point_at(t),
+        }
+    }
+
+    /// Returns the direction (derivative) of the segment at the provided
+    /// parameter `t`.
+    ///
+    /// Falls back to the direction from the starting to the ending point
+    /// where the derivative is zero, like at a control point which
+    /// coincides with an end point.
+    pub fn direction_at(&self, t: f32) -> Vector2<f32> {
+        let (direction, chord) = match self {
+            Segment::Line(l) => (l.to - l.from, l.to - l.from),
+            Segment::Quad(q) => {
+                let direction = 2.0 * (1.0 - t) * (q.ctrl - q.from)
+                    + 2.0 * t * (q.to - q.ctrl);
+                (direction, q.to - q.from)
+            }
+            Segment::Curve(c) => {
+                let u = 1.0 - t;
+                let direction = 3.0 * u * u * (c.ctrl1 - c.from)
+                    + 6.0 * u * t * (c.ctrl2 - c.ctrl1)
+                    + 3.0 * t * t * (c.to - c.ctrl2);
+                (direction, c.to - c.from)
+            }
+            Segment::Custom(c) => {
+                (c.direction_at(t), c.point_at(1.0) - c.point_at(0.0))
+            }
+        };
+
+        if direction.is_zero() {
+            chord
+        } else {
+            direction
+        }
+    }
+
+    #[inline]
+    pub fn start(&self) -> Vector2<f32> {
+        match self {
+            Segment::Line(l) => l.from,
+            Segment::Quad(q) => q.from,
+            Segment::Curve(c) => c.from,
+            Segment::Custom(c) => c.point_at(0.0),
+        }
+    }
+
+    #[inline]
+    pub fn end(&self) -> Vector2<f32> {
+        match self {
+            Segment::Line(l) => l.to,
+            Segment::Quad(q) => q.to,
+            Segment::Curve(c) => c.to,
+            Segment::Custom(c) => c.point_at(1.0),
+        }
+    }
+
+    /// Returns an upper bound of how far the segment strays from the line
+    /// between its end points.
+    fn chord_deviation(&self) -> f32 {
+        let (from, to) = (self.start(), self.end());
+        match self {
+            Segment::Line(_) => 0.0,
+            // A curve stays within its control polygon and reaches half of
+            // the distance of a quadratic control point and three quarters
+            // of the further cubic control point.
+            Segment::Quad(q) => 0.5 * distance_to_line(q.ctrl, from, to),
+            Segment::Curve(c) => {
+                0.75 * distance_to_line(c.ctrl1, from, to)
+                    .max(distance_to_line(c.ctrl2, from, to))
             }
+            Segment::Custom(_) => f32::INFINITY,
         }
-        // Highest y point of the shape.
-        let top = *y_iter
-            .iter()
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .expect("No segments? Impossible!") as i32;
-        let bottom = *y_iter
-            .iter()
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-            .expect("No segments? Impossible!") as i32;
-        let left = *x_iter
-            .iter()
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-            .expect("No segments? Impossible!") as i32;
-        let right = *x_iter
-            .iter()
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .expect("No segments? Impossible!") as i32;
+    }
 
-        BBox {
-            tl: Vector2::new(left, top),
-            br: Vector2::new(right, bottom),
+    /// Returns a copy of the segment with all points mapped through the
+    /// `transform`.
+    fn transformed(&self, transform: &Affine2) -> Segment {
+        let map = |point| transform.transform(point);
+        match self {
+            Segment::Line(l) => {
+                Segment::Line(Line::new(map(l.from), map(l.to)))
+            }
+            Segment::Quad(q) => {
+                Segment::Quad(Quad::new(map(q.from), map(q.ctrl), map(q.to)))
+            }
+            Segment::Curve(c) => Segment::Curve(Curve::new(
+                map(c.from),
+                map(c.ctrl1),
+                map(c.ctrl2),
+                map(c.to),
+            )),
+            Segment::Custom(c) => Segment::Custom(c.transformed(transform)),
         }
     }
-}
 
-#[derive(Debug)]
-pub struct Contour {
-    pub segments: Vec<Segment>,
-    winding: Winding,
-}
+    /// Returns a copy of the segment.
+    fn copied(&self) -> Segment {
+        match self {
+            Segment::Line(l) => Segment::Line(Line::new(l.from, l.to)),
+            Segment::Quad(q) => Segment::Quad(Quad::new(q.from, q.ctrl, q.to)),
+            Segment::Curve(c) => {
+                Segment::Curve(Curve::new(c.from, c.ctrl1, c.ctrl2, c.to))
+            }
+            Segment::Custom(c) => {
+                Segment::Custom(c.transformed(&Affine2::IDENTITY))
+            }
+        }
+    }
 
-impl Contour {
-    pub fn new(segments: Vec<Segment>, winding: Winding) -> Self {
-        Self { segments, winding }
+    /// Returns a copy of the segment with all points scaled by `factor`.
+    fn scaled(&self, factor: Scale) -> Segment {
+        match self {
+            Segment::Line(l) => {
+                let mut line = Line::new(l.from, l.to);
+                line.rescale(factor);
+                Segment::Line(line)
+            }
+            Segment::Quad(q) => {
+                let mut quad = Quad::new(q.from, q.ctrl, q.to);
+                quad.rescale(factor);
+                Segment::Quad(quad)
+            }
+            Segment::Curve(c) => {
+                let mut curve = Curve::new(c.from, c.ctrl1, c.ctrl2, c.to);
+                curve.rescale(factor);
+                Segment::Curve(curve)
+            }
+            Segment::Custom(c) => Segment::Custom(
+                c.transformed(&Affine2::scale(factor.x, factor.y)),
+            ),
+        }
     }
 
-    /// Returns the [`Distance`] to the provided point.
-    pub fn distance(&self, point: Vector2<f32>) -> Distance {
-        self.segments
-            .iter()
-            .map(|segment| segment.distance(point))
-            .reduce(|accum, item| {
-                // To learn more about the comparison go to `SignedDistance::partial_cmp`
-                if accum < item {
-                    accum
-                } else {
-                    item
-                }
-            })
-            .expect("No distances?? Somehow resolve this error if it happens")
+    /// Swaps the starting and the ending point of the segment.
+    pub(crate) fn reverse(&mut self) {
+        match self {
+            Segment::Line(l) => std::mem::swap(&mut l.from, &mut l.to),
+            Segment::Quad(q) => std::mem::swap(&mut q.from, &mut q.to),
+            Segment::Curve(c) => {
+                std::mem::swap(&mut c.from, &mut c.to);
+                std::mem::swap(&mut c.ctrl1, &mut c.ctrl2);
+            }
+            Segment::Custom(c) => *c = c.reversed(),
+        }
     }
 
-    #[inline]
-    pub fn iter(&self) -> Iter<'_, Segment> {
-        self.segments.iter()
+    /// Returns a bounding box tightly surrounding the segment, including the
+    /// extrema of curves, rounded outwards to whole pixels.
+    pub fn bbox(&self) -> BBox {
+        let (min, max) = self.bounds();
+
+        BBox {
+            tl: Vector2::new(min.x.floor() as i32, min.y.floor() as i32),
+            br: Vector2::new(max.x.ceil() as i32, max.y.ceil() as i32),
+        }
     }
 
-    #[inline]
-    pub fn winding(&self) -> Winding {
-        self.winding
+    /// Returns the smallest and the largest coordinates of the segment body.
+    ///
+    /// Besides the end points only the extrema of a curve, where its
+    /// derivative along an axis is zero, can lie on the bounds.
+    pub(crate) fn bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        if let Segment::Custom(c) = self {
+            let bbox = c.bbox();
+            return (
+                Vector2::new(bbox.tl.x as f32, bbox.tl.y as f32),
+                Vector2::new(bbox.br.x as f32, bbox.br.y as f32),
+            );
+        }
+
+        let mut min = self.start();
+        let mut max = self.start();
+        let mut include = |p: Vector2<f32>| {
+            min = Vector2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vector2::new(max.x.max(p.x), max.y.max(p.y));
+        };
+        include(self.end());
+
+        match self {
+            Segment::Line(_) | Segment::Custom(_) => (),
+            Segment::Quad(q) => {
+                // Derivative: 2(p0 - 2p1 + p2)t + 2(p1 - p0) = 0
+                let a = q.from - 2.0 * q.ctrl + q.to;
+                let b = q.ctrl - q.from;
+                for t in [-b.x / a.x, -b.y / a.y] {
+                    if t > 0.0 && t < 1.0 {
+                        let u = 1.0 - t;
+                        include(
+                            u * u * q.from
+                                + 2.0 * u * t * q.ctrl
+                                + t * t * q.to,
+                        );
+                    }
+                }
+            }
+            Segment::Curve(c) => {
+                // Derivative divided by 3: at^2 + 2bt + c = 0
+                let a = -1.0 * c.from + 3.0 * c.ctrl1 - 3.0 * c.ctrl2 + c.to;
+                let b = c.from - 2.0 * c.ctrl1 + c.ctrl2;
+                let d = c.ctrl1 - c.from;
+                let roots_x = crate::math::quadratic_roots(a.x, 2.0 * b.x, d.x);
+                let roots_y = crate::math::quadratic_roots(a.y, 2.0 * b.y, d.y);
+                for t in roots_x.into_iter().chain(roots_y).flatten() {
+                    if t > 0.0 && t < 1.0 {
+                        let u = 1.0 - t;
+                        include(
+                            u * u * u * c.from
+                                + 3.0 * u * u * t * c.ctrl1
+                                + 3.0 * u * t * t * c.ctrl2
+                                + t * t * t * c.to,
+                        );
+                    }
+                }
+            }
+        }
+
+        (min, max)
     }
-}
 
-#[derive(Debug)]
-pub enum Segment {
-    Line(Line),
-    Quad(Quad),
-    Curve(Curve),
-}
+    /// Calls `f` with every point of the segment, including control points.
+    ///
+    /// Custom segments don't have control points so a few points along
+    /// the segment are used instead.
+    pub(crate) fn for_each_point(&self, f: impl FnMut(Vector2<f32>)) {
+        match self {
+            Segment::Line(l) => [l.from, l.to].into_iter().for_each(f),
+            Segment::Quad(q) => [q.from, q.ctrl, q.to].into_iter().for_each(f),
+            Segment::Curve(c) => {
+                [c.from, c.ctrl1, c.ctrl2, c.to].into_iter().for_each(f)
+            }
+            Segment::Custom(c) => [0.0, 0.25, 0.5, 0.75, 1.0]
+                .into_iter()
+                .map(|t| c.point_at(t))
+                .for_each(f),
+        }
+    }
 
-impl Segment {
-    fn distance(&self, point: Vector2<f32>) -> Distance {
+    /// Checks if all points of the segment are at the same position,
+    /// making the segment zero-length.
+    pub fn is_degenerate(&self) -> bool {
         match self {
-            Segment::Line(l) => l.calculate_distance(point),
-            Segment::Quad(q) => q.calculate_distance(point),
-            Segment::Curve(c) => c.calculate_distance(point),
+            Segment::Line(l) => l.from == l.to,
+            Segment::Quad(q) => q.from == q.ctrl && q.ctrl == q.to,
+            Segment::Curve(c) => {
+                c.from == c.ctrl1 && c.ctrl1 == c.ctrl2 && c.ctrl2 == c.to
+            }
+            Segment::Custom(_) => {
+                let start = self.start();
+                let mut degenerate = true;
+                self.for_each_point(|p| degenerate &= p == start);
+                degenerate
+            }
         }
     }
 }
@@ -159,13 +1712,42 @@ impl Line {
         crate::math::line_signed_distance(self, point)
     }
 
-    // TODO explain
+    /// Returns the parameter `t`, clamped to `[0, 1]`, of the point on the
+    /// line closest to the provided point.
+    pub fn nearest_t(&self, point: Vector2<f32>) -> f32 {
+        let direction = self.to - self.from;
+        let length2 = direction.magnitude2();
+        if length2 == 0.0 {
+            return 0.0;
+        }
+        ((point - self.from).dot(direction) / length2).clamp(0.0, 1.0)
+    }
+
+    /// Returns the term of the line in the shoelace formula, twice the
+    /// signed area between the line and the origin.
     #[inline]
     pub fn shoelace(&self) -> f32 {
         self.from.cross(self.to)
     }
 }
 
+/// Returns the distance from the point to the line segment between `from`
+/// and `to`.
+fn distance_to_line(
+    point: Vector2<f32>,
+    from: Vector2<f32>,
+    to: Vector2<f32>,
+) -> f32 {
+    let direction = to - from;
+    let length2 = direction.magnitude2();
+    let t = if length2 == 0.0 {
+        0.0
+    } else {
+        ((point - from).dot(direction) / length2).clamp(0.0, 1.0)
+    };
+    (point - (from + t * direction)).magnitude()
+}
+
 /// Represents a parametric `quadratic` parabola with constants:
 ///
 /// - `from` - starting point
@@ -197,10 +1779,27 @@ impl Quad {
         crate::math::quad_signed_distance(self, point)
     }
 
-    // TODO explain
+    /// Returns the parameter `t`, clamped to `[0, 1]`, of the point on the
+    /// curve closest to the provided point.
+    pub fn nearest_t(&self, point: Vector2<f32>) -> f32 {
+        crate::math::quad_nearest_t(self, point)
+    }
+
+    /// Returns the term of the curve in the shoelace formula, twice the
+    /// signed area between the curve and the origin.
+    ///
+    /// The area between the curve and its chord is added to the term of
+    /// the chord. A control point on one of the end points makes the curve
+    /// a straight line, which gives exactly the term of [`Line::shoelace`].
     #[inline]
     pub fn shoelace(&self) -> f32 {
-        self.from.cross(self.to)
+        let chord = self.from.cross(self.to);
+        if self.ctrl == self.from || self.ctrl == self.to {
+            return chord;
+        }
+        // The area between a quadratic curve and its chord is two thirds
+        // of the triangle formed with the control point.
+        chord + 2.0 * (self.ctrl - self.from).cross(self.to - self.from) / 3.0
     }
 }
 
@@ -245,6 +1844,12 @@ impl Curve {
         crate::math::curve_signed_distance(self, point)
     }
 
+    /// Returns the parameter `t`, clamped to `[0, 1]`, of the point on the
+    /// curve closest to the provided point.
+    pub fn nearest_t(&self, point: Vector2<f32>) -> f32 {
+        crate::math::curve_nearest_t(self, point)
+    }
+
     // TODO explain
     #[inline]
     pub fn shoelace(&self) -> f32 {
@@ -276,3 +1881,859 @@ impl Winding {
         !self.is_cw()
     }
 }
+
+/// Wraps the shape into a 32 x 32 outline at the unit scale without an
+/// offset.
+#[cfg(test)]
+fn tile_outline(shape: Shape) -> crate::GlyphOutline {
+    crate::GlyphOutline::from_shape(
+        shape,
+        BBox::new(Vector2::ZERO_I32, Vector2::new(32, 32)),
+        Scale::uniform(1.0),
+        crate::Offset::uniform(0.0),
+    )
+}
+
+#[test]
+fn touching_union_test() {
+    use crate::{gen::rect_contour, path::PathBuilder, GlyphOutline, Offset};
+
+    // Two squares sharing the edge at `x = 12`.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    rect_contour(&mut builder, (4.0, 4.0), (12.0, 12.0), true);
+    rect_contour(&mut builder, (12.0, 4.0), (20.0, 12.0), true);
+    let mut shape = builder.build_shape();
+    shape.union();
+
+    assert_eq!(shape.contours.len(), 1);
+    assert_eq!(shape.contours[0].segments.len(), 6);
+
+    let bbox = BBox::new(Vector2::ZERO_I32, Vector2::new(24, 16));
+    let outline = GlyphOutline::from_shape(
+        shape,
+        bbox,
+        Scale::uniform(1.0),
+        Offset::uniform(0.0),
+    );
+    let sdf = outline.generate_sdf(8).unwrap();
+    // Along the former shared edge the field is as deep as anywhere else
+    // in the middle of the rectangle.
+    for x in 10..14 {
+        assert_eq!(sdf.distance(x, 8), sdf.distance(8, 8));
+    }
+}
+
+#[test]
+fn validate_test() {
+    let line = |x0, y0, x1, y1| {
+        Segment::Line(Line::new(Vector2::new(x0, y0), Vector2::new(x1, y1)))
+    };
+    let shape = Shape::new(vec![
+        // Valid triangle.
+        Contour::new(
+            vec![
+                line(0.0, 0.0, 8.0, 0.0),
+                line(8.0, 0.0, 4.0, 8.0),
+                line(4.0, 8.0, 0.0, 0.0),
+            ],
+            Winding(true),
+        ),
+        // Open and with a zero-length segment.
+        Contour::new(
+            vec![
+                line(0.0, 0.0, 8.0, 0.0),
+                line(8.0, 0.0, 8.0, 0.0),
+                line(8.0, 0.0, 8.0, 8.0),
+            ],
+            Winding(true),
+        ),
+        // Going back and forth.
+        Contour::new(
+            vec![line(0.0, 0.0, 8.0, 8.0), line(8.0, 8.0, 0.0, 0.0)],
+            Winding(true),
+        ),
+        // Broken coordinate.
+        Contour::new(
+            vec![
+                line(0.0, 0.0, f32::NAN, 0.0),
+                line(f32::NAN, 0.0, 4.0, 8.0),
+                line(4.0, 8.0, 0.0, 0.0),
+            ],
+            Winding(true),
+        ),
+        Contour::new(vec![], Winding(true)),
+    ]);
+
+    assert_eq!(
+        shape.validate(),
+        vec![
+            ShapeIssue::DegenerateSegment {
+                contour: 1,
+                segment: 1
+            },
+            ShapeIssue::OpenContour { contour: 1 },
+            ShapeIssue::ZeroArea { contour: 2 },
+            ShapeIssue::NonFiniteCoordinate {
+                contour: 3,
+                segment: 0
+            },
+            ShapeIssue::NonFiniteCoordinate {
+                contour: 3,
+                segment: 1
+            },
+            ShapeIssue::EmptyContour { contour: 4 },
+        ]
+    );
+}
+
+#[test]
+fn contour_role_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('O')
+        .build(Scale::uniform(32.0), crate::Offset::uniform(2.0));
+    let roles = outline
+        .shape
+        .contours_with_role()
+        .map(|(_, fill)| fill)
+        .collect::<Vec<_>>();
+
+    assert_eq!(roles.len(), 2);
+    assert_eq!(roles.iter().filter(|&&fill| fill).count(), 1);
+    // The outer contour of Roboto's 'O' has more segments than the hole.
+    let outer = outline
+        .shape
+        .contours_with_role()
+        .max_by_key(|(contour, _)| contour.segments.len())
+        .unwrap();
+    assert!(outer.1);
+}
+
+#[test]
+fn segment_bbox_test() {
+    let quad = Segment::Quad(Quad::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(10.0, 20.0),
+        Vector2::new(20.0, 0.0),
+    ));
+    let (min, max) = quad.bounds();
+    // The top of the parabola is in the middle between the end points and
+    // the control point.
+    assert_eq!(min, Vector2::new(0.0, 0.0));
+    assert_eq!(max, Vector2::new(20.0, 10.0));
+
+    let control = BBox::new(Vector2::new(0, 0), Vector2::new(20, 20));
+    let bbox = quad.bbox();
+    assert!(bbox.tl.x >= control.tl.x && bbox.tl.y >= control.tl.y);
+    assert!(bbox.br.x <= control.br.x && bbox.br.y <= control.br.y);
+    assert!(bbox.height() < control.height());
+
+    // Curve extrema on both axes.
+    let curve = Segment::Curve(Curve::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(-10.0, 10.0),
+        Vector2::new(30.0, 10.0),
+        Vector2::new(20.0, 0.0),
+    ));
+    let (min, max) = curve.bounds();
+    assert!(min.x < 0.0 && min.x > -10.0);
+    assert!(max.x > 20.0 && max.x < 30.0);
+    assert!((max.y - 7.5).abs() < 1e-4);
+}
+
+#[test]
+fn custom_segment_test() {
+    /// Elliptic arc from `start` to `end` angle in radians, going through
+    /// `center + axis_x` at the angle zero and `center + axis_y` at a
+    /// quarter turn.
+    #[derive(Debug)]
+    struct Arc {
+        center: Vector2<f32>,
+        axis_x: Vector2<f32>,
+        axis_y: Vector2<f32>,
+        start: f32,
+        end: f32,
+    }
+
+    impl Arc {
+        fn angle_at(&self, t: f32) -> f32 {
+            self.start + t * (self.end - self.start)
+        }
+    }
+
+    impl SignedDistanceSegment for Arc {
+        fn distance(&self, point: Vector2<f32>) -> Distance {
+            let t = self.nearest_t(point);
+            let closest_point = point - self.point_at(t);
+            let ortho = if closest_point.is_zero() {
+                0.0
+            } else {
+                self.direction_at(t)
+                    .normalize()
+                    .cross(closest_point.normalize())
+            };
+
+            Distance {
+                extended_dist: closest_point.magnitude(),
+                real_dist: closest_point.magnitude(),
+                orthogonality: ortho.abs(),
+                sign: ortho.signum(),
+            }
+        }
+
+        fn bbox(&self) -> BBox {
+            let extent = Vector2::new(
+                self.axis_x.x.hypot(self.axis_y.x),
+                self.axis_x.y.hypot(self.axis_y.y),
+            );
+            let (min, max) = (self.center - extent, self.center + extent);
+            BBox::new(
+                Vector2::new(min.x.floor() as i32, min.y.floor() as i32),
+                Vector2::new(max.x.ceil() as i32, max.y.ceil() as i32),
+            )
+        }
+
+        fn point_at(&self, t: f32) -> Vector2<f32> {
+            let angle = self.angle_at(t);
+            self.center + angle.cos() * self.axis_x + angle.sin() * self.axis_y
+        }
+
+        fn direction_at(&self, t: f32) -> Vector2<f32> {
+            let angle = self.angle_at(t);
+            (self.end - self.start)
+                * (angle.cos() * self.axis_y - angle.sin() * self.axis_x)
+        }
+
+        fn transformed(
+            &self,
+            transform: &Affine2,
+        ) -> Box<dyn SignedDistanceSegment> {
+            let linear = Affine2 {
+                tx: 0.0,
+                ty: 0.0,
+                ..*transform
+            };
+            Box::new(Arc {
+                center: transform.transform(self.center),
+                axis_x: linear.transform(self.axis_x),
+                axis_y: linear.transform(self.axis_y),
+                ..*self
+            })
+        }
+
+        fn reversed(&self) -> Box<dyn SignedDistanceSegment> {
+            Box::new(Arc {
+                start: self.end,
+                end: self.start,
+                ..*self
+            })
+        }
+
+        fn part(&self, t0: f32, t1: f32) -> Box<dyn SignedDistanceSegment> {
+            Box::new(Arc {
+                start: self.angle_at(t0),
+                end: self.angle_at(t1),
+                ..*self
+            })
+        }
+    }
+
+    let circle = Arc {
+        center: Vector2::new(16.0, 16.0),
+        axis_x: Vector2::new(10.0, 0.0),
+        axis_y: Vector2::new(0.0, 10.0),
+        start: 0.0,
+        end: std::f32::consts::TAU,
+    };
+    let contour =
+        Contour::new(vec![Segment::Custom(Box::new(circle))], Winding(true));
+    let shape = Shape::new(vec![contour]);
+    assert!(shape.validate().is_empty());
+    let bbox = shape.bbox();
+    assert_eq!(bbox, BBox::new(Vector2::new(6, 6), Vector2::new(26, 26)));
+
+    // Transforming the shape transforms the custom segments too.
+    let scaled = shape.rescale(Scale::uniform(1.0), Scale::uniform(2.0));
+    let (closest, distance) = scaled.closest_point(Vector2::new(60.0, 32.0));
+    assert!((closest - Vector2::new(52.0, 32.0)).magnitude() < 1e-2);
+    assert!((distance + 8.0).abs() < 1e-3);
+    let skewed = shape.deskew(-45.0);
+    assert!(skewed.sign_at(Vector2::new(32.0, 17.0)) > 0.0);
+    assert!(skewed.sign_at(Vector2::new(40.0, 24.0)) > 0.0);
+    assert!(skewed.sign_at(Vector2::new(16.0, 17.0)) < 0.0);
+
+    // The circle has no corners to round off and is simplified to itself.
+    assert_eq!(shape.round_corners(1.0).contours[0].segments.len(), 1);
+    assert_eq!(shape.simplify(0.5).contours[0].segments.len(), 1);
+    let (fills, holes) = shape.split_fills_holes();
+    assert_eq!((fills.len(), holes.len()), (1, 0));
+
+    // A counter-clockwise circle is turned clockwise again.
+    let mut reversed = shape.rescale(Scale::uniform(1.0), Scale::uniform(1.0));
+    reversed.contours[0].reverse();
+    reversed.contours[0].winding = Winding(false);
+    let point = reversed.contours[0].segments[0].point_at(0.25);
+    assert!((point - Vector2::new(16.0, 6.0)).magnitude() < 1e-4);
+    assert_eq!(reversed.normalize_windings(), vec![Winding(true)]);
+    let point = reversed.contours[0].segments[0].point_at(0.25);
+    assert!((point - Vector2::new(16.0, 26.0)).magnitude() < 1e-4);
+
+    let outline = tile_outline(shape);
+    let sdf = outline.generate_sdf(8).unwrap();
+    for (x, y) in [(16, 16), (10, 20), (22, 13), (2, 2), (30, 16)] {
+        let center = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+        let expected = (10.0 - (center - Vector2::new(16.0, 16.0)).magnitude())
+            .clamp(-4.0, 4.0);
+        assert!((sdf.distance(x, y) - expected).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn closest_point_test() {
+    use crate::{gen::rect_contour, path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    rect_contour(&mut builder, (4.0, 4.0), (28.0, 20.0), true);
+    let shape = builder.build_shape();
+
+    // Outside of the right edge.
+    let (closest, distance) = shape.closest_point(Vector2::new(31.0, 12.0));
+    assert!((closest.x - 28.0).abs() < 1e-4);
+    assert!((closest.y - 12.0).abs() < 1e-4);
+    assert!((distance + 3.0).abs() < 1e-4);
+
+    // Inside, closest to the top edge.
+    let (closest, distance) = shape.closest_point(Vector2::new(10.0, 6.0));
+    assert!((closest.x - 10.0).abs() < 1e-4);
+    assert!((closest.y - 4.0).abs() < 1e-4);
+    assert!((distance - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn hole_first_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    // The subtractive contour comes before the filled one.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 12.0, 20.0, false);
+    square_contour(&mut builder, 4.0, 28.0, true);
+    let shape = builder.build_shape();
+    assert_eq!(
+        shape
+            .contours_with_role()
+            .map(|(_, f)| f)
+            .collect::<Vec<_>>(),
+        vec![false, true]
+    );
+
+    assert_eq!(shape.winding_number(Vector2::new(8.0, 8.0)), 1);
+    assert_eq!(shape.winding_number(Vector2::new(16.0, 16.0)), 0);
+    assert_eq!(shape.winding_number(Vector2::new(2.0, 16.0)), 0);
+
+    let outline = tile_outline(shape);
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert!(sdf.distance(16, 16) < 0.0);
+    assert!(sdf.distance(8, 8) > 0.0);
+    assert!(sdf.distance(1, 16) < 0.0);
+}
+
+#[test]
+fn nesting_depths_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    // A fill containing a hole containing a fill, listed from the inside
+    // out, next to a separate fill.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 12.0, 20.0, true);
+    square_contour(&mut builder, 8.0, 24.0, false);
+    square_contour(&mut builder, 4.0, 28.0, true);
+    square_contour(&mut builder, 40.0, 48.0, true);
+    let shape = builder.build_shape();
+    assert_eq!(shape.nesting_depths(), vec![2, 1, 0, 0]);
+}
+
+#[test]
+fn same_direction_windings_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    // Both contours of the `O` go clockwise, like in a broken font.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 4.0, 28.0, true);
+    square_contour(&mut builder, 12.0, 20.0, true);
+    let mut shape = builder.build_shape();
+    let hole = Vector2::new(16.0, 16.0);
+    assert!(shape.contours.iter().all(|c| c.winding().is_cw()));
+    assert_eq!(shape.winding_number(hole), 2);
+
+    let expected = vec![Winding(true), Winding(false)];
+    assert_eq!(shape.normalized_windings(), expected);
+    assert_eq!(shape.normalize_windings(), expected);
+    assert_eq!(shape.winding_number(hole), 0);
+    assert_eq!(shape.winding_number(Vector2::new(8.0, 8.0)), 1);
+
+    let outline = tile_outline(shape);
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert!(sdf.distance(16, 16) < 0.0);
+    assert!(sdf.distance(8, 8) > 0.0);
+}
+
+#[test]
+fn degenerate_quad_shoelace_test() {
+    let (from, to) = (Vector2::new(1.5, 7.25), Vector2::new(13.0, -2.75));
+    let line = Line::new(from, to);
+    assert_eq!(Quad::new(from, from, to).shoelace(), line.shoelace());
+    assert_eq!(Quad::new(from, to, to).shoelace(), line.shoelace());
+
+    // A proper curve adds the area between itself and its chord.
+    let quad = Quad::new(from, Vector2::new(10.0, 9.0), to);
+    let segment = Segment::Quad(Quad::new(quad.from, quad.ctrl, quad.to));
+    assert!((quad.shoelace() - line.shoelace()).abs() > 1.0);
+    assert!((2.0 * segment.area() - quad.shoelace()).abs() < 1e-4);
+}
+
+#[test]
+fn split_fills_holes_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('O')
+        .build(Scale::uniform(32.0), crate::Offset::uniform(2.0));
+    let (fills, holes) = outline.shape.split_fills_holes();
+    assert_eq!((fills.len(), holes.len()), (1, 1));
+    assert!(fills[0].winding().is_cw());
+    assert!(holes[0].winding().is_ccw());
+
+    // The hole lies inside of the fill.
+    let center = holes[0].segments[0].point_at(0.5);
+    assert_ne!(fills[0].winding_number(center), 0);
+    assert!(fills[0].signed_area().abs() > holes[0].signed_area().abs());
+
+    // Overlapping contours going the same way are both fills.
+    let mut builder =
+        crate::path::PathBuilder::new(crate::Offset::uniform(0.0));
+    crate::gen::square_contour(&mut builder, 2.0, 14.0, true);
+    crate::gen::square_contour(&mut builder, 8.0, 20.0, true);
+    let (fills, holes) = builder.build_shape().split_fills_holes();
+    assert_eq!((fills.len(), holes.len()), (2, 0));
+}
+
+#[test]
+fn overlapping_windings_test() {
+    use crate::{gen::rect_contour, path::PathBuilder, Offset};
+
+    // A plus made of two clockwise bars crossing each other, like the
+    // overlapping parts of a variable font glyph.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    rect_contour(&mut builder, (4.0, 12.0), (28.0, 20.0), true);
+    rect_contour(&mut builder, (12.0, 4.0), (20.0, 28.0), true);
+    let mut shape = builder.build_shape();
+    let center = Vector2::new(16.0, 16.0);
+    assert_eq!(shape.winding_number(center), 2);
+    assert_eq!(shape.nesting_depths(), vec![0, 0]);
+
+    let expected = vec![Winding(true), Winding(true)];
+    assert_eq!(shape.normalize_windings(), expected);
+    assert_eq!(shape.winding_number(center), 2);
+}
+
+#[test]
+fn on_edge_sign_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 2.0, 14.0, true);
+    let shape = builder.build_shape();
+    let contour = &shape.contours[0];
+
+    // The side of a segment is undefined on the segment and its extension.
+    let top = &contour.segments[0];
+    assert_eq!(top.distance(Vector2::new(8.0, 2.0)).sign, 0.0);
+    assert_eq!(top.distance(Vector2::new(20.0, 2.0)).sign, 0.0);
+
+    for point in [Vector2::new(8.0, 2.0), Vector2::new(14.0, 8.0)] {
+        let distance = contour.distance(point);
+        let inside = contour.winding_number(point) != 0;
+        assert_eq!(distance.sign, if inside { 1.0 } else { -1.0 });
+        assert_eq!(distance.real_signed(), 0.0);
+        let (_, closest) = contour.closest_segment(point).unwrap();
+        assert_eq!(closest.sign, distance.sign);
+    }
+    // Points just off the edge keep the side of the segment.
+    assert_eq!(contour.distance(Vector2::new(8.0, 2.01)).sign, 1.0);
+    assert_eq!(contour.distance(Vector2::new(8.0, 1.99)).sign, -1.0);
+}
+
+#[test]
+fn open_contour_test() {
+    use crate::{path::ShapeBuilder, GlyphOutline, Offset};
+
+    // L-shaped centerline going down and then to the right.
+    let mut builder = ShapeBuilder::new(24, 24, None, Offset::uniform(0.0));
+    builder.open_at(6.0, 4.0);
+    builder.line_to(6.0, 18.0);
+    builder.line_to(20.0, 18.0);
+    builder.end_open();
+    let (shape, bbox) = builder.build();
+    assert!(shape.contours[0].is_open());
+    assert!(shape.validate().is_empty());
+
+    let outline = GlyphOutline::from_shape(
+        shape,
+        bbox,
+        Scale::uniform(1.0),
+        Offset::uniform(0.0),
+    );
+    let range = 8;
+    let sdf = outline.generate_sdf(range).unwrap();
+    let path_distance = |x: f32, y: f32| {
+        let vertical = (x - 6.0).hypot(y - y.clamp(4.0, 18.0));
+        let horizontal = (x - x.clamp(6.0, 20.0)).hypot(y - 18.0);
+        vertical.min(horizontal)
+    };
+    for y in 0..sdf.height {
+        for x in 0..sdf.width {
+            let expected = path_distance(x as f32 + 0.5, y as f32 + 0.5)
+                .min(0.5 * range as f32);
+            assert!((sdf.distance(x, y) + expected).abs() < 1e-4);
+        }
+    }
+
+    // Stroking the centerline fills the pixels close to it.
+    let stroked = sdf.stroke(1.0);
+    assert!(stroked.distance(6, 10) > 0.0);
+    assert!(stroked.distance(12, 17) > 0.0);
+    assert!(stroked.distance(12, 10) < 0.0);
+}
+
+#[test]
+fn flatten_test() {
+    use crate::{Font, GlyphOutline, Offset};
+
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let outline = font
+        .glyph('S')
+        .build(Scale::uniform(64.0), Offset::uniform(3.0));
+    let sdf = outline.generate_sdf(6).unwrap();
+
+    for tolerance in [0.5, 0.1, 0.02] {
+        let flat = outline.shape.flatten(tolerance);
+        assert!(flat
+            .contours
+            .iter()
+            .flat_map(Contour::iter)
+            .all(|segment| matches!(segment, Segment::Line(_))));
+
+        let flat = GlyphOutline::from_shape(
+            flat,
+            outline.bbox(),
+            outline.scale(),
+            Offset::uniform(3.0),
+        );
+        let diff = flat.generate_sdf(6).unwrap().diff(&sdf);
+        assert!(diff.max_abs <= tolerance + 1e-3);
+    }
+}
+
+#[test]
+fn closest_feature_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 0.0, 10.0, true);
+    builder.open_at(20.0, 0.0);
+    builder.line_to(30.0, 0.0);
+    builder.quad_to(30.0, 10.0, 20.0, 10.0);
+    builder.line_to(20.0, 0.0);
+    builder.close();
+    let shape = builder.build_shape();
+
+    // Just outside of the right edge of the first square, a quarter of the
+    // way down.
+    let (contour, segment, t, distance) =
+        shape.closest_feature(Vector2::new(11.0, 2.5));
+    assert_eq!((contour, segment), (0, 1));
+    assert!((t - 0.25).abs() < 1e-6);
+    assert!((distance.real_dist - 1.0).abs() < 1e-6);
+
+    // Next to the start of the curve in the second contour.
+    let (contour, segment, t, distance) =
+        shape.closest_feature(Vector2::new(30.5, 2.0));
+    assert_eq!((contour, segment), (1, 1));
+    let closest = shape.contours[1].segments[1].point_at(t);
+    assert!(
+        (distance.real_dist - (closest - Vector2::new(30.5, 2.0)).magnitude())
+            .abs()
+            < 1e-4
+    );
+}
+
+#[test]
+fn cubic_closest_point_test() {
+    use crate::{path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(4.0, 20.0);
+    builder.curve_to(4.0, 4.0, 28.0, 4.0, 28.0, 20.0);
+    builder.line_to(4.0, 20.0);
+    builder.close();
+    let shape = builder.build_shape();
+    let curve = &shape.contours[0].segments[0];
+
+    for point in [
+        Vector2::new(16.0, 2.0),
+        Vector2::new(16.0, 12.0),
+        Vector2::new(2.0, 10.0),
+        Vector2::new(30.0, 18.0),
+    ] {
+        let nearest = (0..=1000)
+            .map(|i| curve.point_at(i as f32 / 1000.0))
+            .map(|p| (p - point).magnitude())
+            .fold(f32::MAX, f32::min);
+
+        let (closest, distance) = shape.closest_point(point);
+        assert!((distance.abs() - nearest).abs() < 1e-2);
+        assert!(((closest - point).magnitude() - nearest).abs() < 1e-2);
+        assert_eq!(distance > 0.0, shape.sign_at(point) > 0.0);
+
+        let (contour, segment, t, distance) = shape.closest_feature(point);
+        assert_eq!((contour, segment), (0, 0));
+        assert!((distance.real_dist - nearest).abs() < 1e-2);
+        assert!(
+            ((curve.point_at(t) - point).magnitude() - nearest).abs() < 1e-2
+        );
+    }
+}
+
+#[test]
+fn merge_test() {
+    use crate::{gen::rect_contour, path::PathBuilder, Offset};
+
+    let square = |x: f32| {
+        let mut builder = PathBuilder::new(Offset::uniform(0.0));
+        rect_contour(&mut builder, (x, 0.0), (x + 4.0, 4.0), true);
+        builder.build_shape()
+    };
+
+    let merged = Shape::merge(vec![square(0.0), square(10.0)]);
+    assert_eq!(merged.contours.len(), 2);
+    assert_eq!(
+        merged.contours[1].segments[0].start(),
+        Vector2::new(10.0, 0.0)
+    );
+    assert_eq!(merged.winding_number(Vector2::new(2.0, 2.0)), 1);
+    assert_eq!(merged.winding_number(Vector2::new(12.0, 2.0)), 1);
+
+    let mut appended = square(0.0);
+    appended.append(square(10.0));
+    assert_eq!(appended.segment_count(), merged.segment_count());
+}
+
+#[test]
+fn simplify_test() {
+    use crate::{path::PathBuilder, Offset};
+
+    // A square with every side split into ten lines, starting in the
+    // middle of the top side and slightly wobbling.
+    let corners = [(10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)];
+    let mut builder = PathBuilder::new(Offset::uniform(2.0));
+    builder.open_at(5.0, 0.0);
+    let mut previous = (5.0, 0.0);
+    for (i, &corner) in corners.iter().chain([(5.0, 0.0)].iter()).enumerate() {
+        let steps = if i == 0 || i == 4 { 5 } else { 10 };
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let wobble = if step % 2 == 0 { 0.01 } else { -0.01 };
+            let x = previous.0 + (corner.0 - previous.0) * t;
+            let y = previous.1 + (corner.1 - previous.1) * t;
+            if step == steps {
+                builder.line_to(x, y);
+            } else {
+                builder.line_to(x + wobble, y + wobble);
+            }
+        }
+        previous = corner;
+    }
+    builder.close();
+    let shape = builder.build_shape();
+    assert_eq!(shape.segment_count(), 40);
+
+    let simple = shape.simplify(0.05);
+    assert_eq!(simple.segment_count(), 4);
+    assert!(simple.contours[0]
+        .iter()
+        .all(|segment| matches!(segment, Segment::Line(_))));
+    assert!((simple.signed_area() - shape.signed_area()).abs() < 0.5);
+    for segment in simple.contours[0].iter() {
+        let Vector2 { x, y } = segment.start();
+        assert!((x == 2.0 || x == 12.0) && (y == 2.0 || y == 12.0));
+    }
+
+    // The wobble is larger than a tighter tolerance, which keeps nearly
+    // all of the points.
+    assert!(shape.simplify(0.001).segment_count() > 30);
+}
+
+#[test]
+fn deskew_test() {
+    use crate::{Font, Offset};
+
+    let font = Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    assert_eq!(font.italic_angle(), 0.0);
+
+    // None of the example fonts is italic, so the `I` is slanted the way
+    // an italic font with the angle of -12 degrees would be.
+    let angle: f32 = -12.0;
+    let upright = font
+        .glyph('I')
+        .build(Scale::uniform(64.0), Offset::uniform(0.0));
+    let italic = font.glyph('I').build_with_transform(
+        &Affine2::scale(64.0, 64.0).then(Affine2::skew_x(-angle.to_radians())),
+        Offset::uniform(0.0),
+    );
+    let stem = upright.shape.bbox().width();
+    assert!(italic.shape.bbox().width() > stem + 5);
+
+    let deskewed = italic.shape.deskew(angle);
+    assert!((deskewed.bbox().width() - stem).abs() <= 1);
+    assert!((deskewed.signed_area() - italic.shape.signed_area()).abs() < 1.0);
+}
+
+#[test]
+fn round_corners_test() {
+    use crate::{path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(2.0, 2.0);
+    builder.line_to(12.0, 2.0);
+    builder.line_to(12.0, 12.0);
+    builder.quad_to(7.0, 16.0, 2.0, 12.0);
+    builder.line_to(2.0, 2.0);
+    builder.close();
+    let shape = builder.build_shape();
+    let rounded = shape.round_corners(2.0);
+
+    let segments = &rounded.contours[0].segments;
+    assert_eq!(segments.len(), 8);
+    for (i, segment) in segments.iter().enumerate() {
+        let next = &segments[(i + 1) % segments.len()];
+        assert!((segment.end() - next.start()).magnitude() < 1e-4);
+        let turn = segment
+            .direction_at(1.0)
+            .angle_between(next.direction_at(0.0));
+        assert!(turn < 1e-3, "Sharp corner after segment {}", i);
+    }
+    // The fillets start the radius away from the corner.
+    assert!((segments[0].end() - Vector2::new(10.0, 2.0)).magnitude() < 1e-4);
+    // The winding and the middle of the shape don't change.
+    assert_eq!(rounded.contours[0].winding(), shape.contours[0].winding());
+    assert_eq!(rounded.winding_number(Vector2::new(7.0, 7.0)), 1);
+    // The former corner is now outside.
+    assert_eq!(rounded.winding_number(Vector2::new(2.1, 2.1)), 0);
+}
+
+#[test]
+fn rescale_test() {
+    let font = crate::Font::from_slice(include_bytes!(
+        "../examples/fonts/Roboto-Regular.ttf"
+    ));
+    let from = Scale::uniform(16.0);
+    let shape = font
+        .glyph('g')
+        .build(from, crate::Offset::uniform(0.0))
+        .shape;
+    let rescaled = shape.rescale(from, Scale::uniform(32.0));
+
+    assert_eq!(rescaled.segment_count(), shape.segment_count());
+    let (min, max) = shape.bounds();
+    let (rescaled_min, rescaled_max) = rescaled.bounds();
+    assert!((rescaled_min - 2.0 * min).magnitude() < 1e-4);
+    assert!((rescaled_max - 2.0 * max).magnitude() < 1e-4);
+    assert!((rescaled.signed_area() - 4.0 * shape.signed_area()).abs() < 1e-2);
+
+    // Axes are scaled independently.
+    let condensed = shape.rescale(from, Scale { x: 8.0, y: 16.0 });
+    let (condensed_min, condensed_max) = condensed.bounds();
+    let width = condensed_max.x - condensed_min.x;
+    assert!((2.0 * width - (max.x - min.x)).abs() < 1e-4);
+    assert_eq!(condensed_max.y - condensed_min.y, max.y - min.y);
+}
+
+#[test]
+fn signed_area_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    // Clockwise square with a side of 10.
+    square_contour(&mut builder, 0.0, 10.0, true);
+    // Counter-clockwise half of a parabola with an area of 2/3 * 4 * 6.
+    builder.open_at(20.0, 0.0);
+    builder.quad_to(23.0, 12.0, 26.0, 0.0);
+    builder.line_to(20.0, 0.0);
+    builder.close();
+    let shape = builder.build_shape();
+
+    assert!((shape.contours[0].signed_area() - 100.0).abs() < 1e-3);
+    assert!((shape.contours[1].signed_area() + 24.0).abs() < 1e-3);
+    assert!((shape.signed_area() - 76.0).abs() < 1e-3);
+    assert_eq!(shape.segment_count(), 6);
+    assert!(!shape.is_empty());
+
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(0.0, 0.0);
+    builder.line_to(10.0, 5.0);
+    builder.line_to(0.0, 0.0);
+    builder.close();
+    assert!(builder.build_shape().is_empty());
+}
+
+#[test]
+fn bowtie_test() {
+    use crate::{path::PathBuilder, Offset};
+
+    // Both lobes have the same area with opposite windings.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    builder.open_at(4.0, 4.0);
+    builder.line_to(28.0, 28.0);
+    builder.line_to(28.0, 4.0);
+    builder.line_to(4.0, 28.0);
+    builder.line_to(4.0, 4.0);
+    builder.close();
+    let shape = builder.build_shape();
+    assert!(shape.signed_area().abs() < 1e-3);
+    assert_eq!(shape.winding_number(Vector2::new(24.0, 16.0)).abs(), 1);
+    assert_eq!(shape.winding_number(Vector2::new(8.0, 16.0)).abs(), 1);
+    assert!(!shape.is_empty());
+
+    let outline = tile_outline(shape);
+    let sdf = outline.generate_sdf(4).unwrap();
+    assert!(sdf.distance(24, 16) > 0.0);
+    assert!(sdf.distance(7, 16) > 0.0);
+    assert!(sdf.distance(16, 6) < 0.0);
+}
+
+#[test]
+fn sample_outline_test() {
+    use crate::{gen::square_contour, path::PathBuilder, Offset};
+
+    // Square with a perimeter of 1.
+    let mut builder = PathBuilder::new(Offset::uniform(0.0));
+    square_contour(&mut builder, 0.0, 0.25, true);
+    let shape = builder.build_shape();
+
+    let points = shape.sample_outline(0.25);
+    assert_eq!(points.len(), 4);
+    let corners = [(0.0, 0.0), (0.25, 0.0), (0.25, 0.25), (0.0, 0.25)];
+    for (point, corner) in points.iter().zip(corners) {
+        assert!((point.x - corner.0).abs() < 1e-5);
+        assert!((point.y - corner.1).abs() < 1e-5);
+    }
+    assert_eq!(shape.sample_outline(0.1).len(), 10);
+    assert_eq!(shape.sample_outline(5.0).len(), 1);
+}
@@ -63,6 +63,133 @@ impl<N: Real> Vector2<N> {
         );
         self * (mag.recip())
     }
+
+    /// Returns the angle of the vector from the positive x axis in radians,
+    /// in the interval `[-PI, PI]`.
+    #[inline]
+    pub fn angle(self) -> N {
+        self.y.atan2(self.x)
+    }
+
+    /// Returns the angle between the two vectors in radians, in the
+    /// interval `[0, PI]`, regardless of their order.
+    #[inline]
+    pub fn angle_between(self, other: Self) -> N {
+        self.cross(other).atan2(self.dot(other)).abs()
+    }
+}
+
+/// Affine transformation of points, a 2×3 matrix combining a linear part,
+/// like a rotation, a scale or a skew, with a translation.
+///
+/// A point is mapped to `(xx * x + xy * y + tx, yx * x + yy * y + ty)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub xx: f32,
+    pub xy: f32,
+    pub yx: f32,
+    pub yy: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Affine2 {
+        xx: 1.0,
+        xy: 0.0,
+        yx: 0.0,
+        yy: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    #[inline]
+    pub fn scale(x: f32, y: f32) -> Self {
+        Affine2 {
+            xx: x,
+            yy: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn translation(x: f32, y: f32) -> Self {
+        Affine2 {
+            tx: x,
+            ty: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotation by `angle` radians, clockwise with the y axis pointing down.
+    #[inline]
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Affine2 {
+            xx: cos,
+            xy: -sin,
+            yx: sin,
+            yy: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Horizontal skew by `angle` radians, which slants the top of an
+    /// upright shape to the right when the y axis points down.
+    #[inline]
+    pub fn skew_x(angle: f32) -> Self {
+        Affine2 {
+            xy: -angle.tan(),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Returns the transformation applying `self` first and `next` after
+    /// it.
+    pub fn then(self, next: Affine2) -> Self {
+        Affine2 {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            yy: next.yx * self.xy + next.yy * self.yy,
+            tx: next.xx * self.tx + next.xy * self.ty + next.tx,
+            ty: next.yx * self.tx + next.yy * self.ty + next.ty,
+        }
+    }
+
+    #[inline]
+    pub fn transform(&self, point: Vector2<f32>) -> Vector2<f32> {
+        Vector2::new(
+            self.xx * point.x + self.xy * point.y + self.tx,
+            self.yx * point.x + self.yy * point.y + self.ty,
+        )
+    }
+
+    /// Returns the determinant of the linear part, which is negative when
+    /// the transformation mirrors the points.
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.xx * self.yy - self.xy * self.yx
+    }
+
+    /// Returns the transformation undoing this one, or [`None`] if the
+    /// linear part collapses the points onto a line or a point.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (xx, xy) = (self.yy / det, -self.xy / det);
+        let (yx, yy) = (-self.yx / det, self.xx / det);
+        Some(Affine2 {
+            xx,
+            xy,
+            yx,
+            yy,
+            tx: -(xx * self.tx + xy * self.ty),
+            ty: -(yx * self.tx + yy * self.ty),
+        })
+    }
 }
 
 impl<N: Num> Add for Vector2<N> {
@@ -184,3 +311,38 @@ fn vec_test() {
     let v1 = Vector2::new(1i32, 10i32);
     assert_eq!(v1.dot(v1), 101);
 }
+
+#[test]
+fn angle_test() {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    assert_eq!(Vector2::new(0.0f32, 1.0).angle(), FRAC_PI_2);
+    assert_eq!(Vector2::new(-1.0f32, 0.0).angle(), PI);
+
+    let x = Vector2::new(2.0f32, 0.0);
+    let y = Vector2::new(0.0f32, 3.0);
+    assert_eq!(x.angle_between(y), FRAC_PI_2);
+    assert_eq!(y.angle_between(x), FRAC_PI_2);
+    assert!(
+        (x.angle_between(Vector2::new(1.0, -1.0)) - FRAC_PI_4).abs() < 1e-6
+    );
+    assert_eq!(x.angle_between(x), 0.0);
+}
+
+#[test]
+fn affine_test() {
+    use std::f32::consts::FRAC_PI_2;
+
+    let point = Vector2::new(2.0, 1.0);
+    let m = Affine2::scale(2.0, 3.0).then(Affine2::translation(1.0, -1.0));
+    assert_eq!(m.transform(point), Vector2::new(5.0, 2.0));
+    assert_eq!(Affine2::IDENTITY.then(m), m);
+
+    let rotated = Affine2::rotation(FRAC_PI_2).transform(point);
+    assert!((rotated - Vector2::new(-1.0, 2.0)).magnitude() < 1e-6);
+    assert_eq!(Affine2::scale(-1.0, 1.0).determinant(), -1.0);
+
+    let inverse = m.inverse().unwrap();
+    assert_eq!(inverse.transform(m.transform(point)), point);
+    assert!(Affine2::scale(0.0, 1.0).inverse().is_none());
+}